@@ -4,18 +4,63 @@ use super::*;
 
 /// Compile and build program command.
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Build {
     #[arg(
         short,
         long,
-        help = "Optional Rust package generation path is created solely in case of build failure"
+        help = "Optional path the Rust packages are generated in, a temporary directory by default. The package of a workflow failing to compile is kept, and its path printed"
     )]
     pub build_dir: Option<PathBuf>,
 
     #[arg(short, long, help = "Optional path to output workflow wasm")]
     pub output: Option<PathBuf>,
 
-    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory 
+    #[arg(
+        short,
+        long,
+        help = "Target to build the workflows for: wasm32-wasip1 by default, wasm32-wasip2, wasm32-unknown-unknown, or native to debug them as host binaries reading their input from stdin"
+    )]
+    pub target: Option<BuildTarget>,
+
+    #[arg(
+        long,
+        help = "Build WebAssembly components exporting the WIT world generated for each workflow, for the wasm32-wasip2 target"
+    )]
+    pub component: bool,
+
+    #[arg(
+        long,
+        help = "Build without accessing the network, from the dependencies vendored by `composer vendor`"
+    )]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        help = "Directory of the vendored dependencies, the vendor directory of the package by default"
+    )]
+    pub vendor_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Build every workflow in its own target directory, instead of the one shared by the workflows with the same dependencies in the cache directory"
+    )]
+    pub no_cache: bool,
+
+    #[arg(
+        long,
+        help = "Rebuild every workflow, even the ones which did not change since their last build"
+    )]
+    pub force: bool,
+
+    #[arg(
+        long,
+        default_value = "artifact",
+        help = "Output of the build: artifact to compile the workflows, or rust to only generate their Rust packages in the build directory, to inspect them"
+    )]
+    pub emit: Emit,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
     pub source: Option<PathBuf>,
 }
 
@@ -27,10 +72,34 @@ impl Execute<Context> for Build {
         let start = Instant::now();
         context.init(self.source, self.build_dir, self.output)?;
         context.parse()?;
-        context.build()?;
+
+        let options = BuildOptions {
+            target: self.target.unwrap_or(if self.component {
+                BuildTarget::Wasip2
+            } else {
+                BuildTarget::default()
+            }),
+            component: self.component,
+            offline: self.offline,
+            vendor: context.vendored_sources(self.vendor_dir)?,
+            cache: if self.no_cache {
+                None
+            } else {
+                BuildOptions::default_cache()
+            },
+            force: self.force,
+            emit: self.emit,
+            workflows: Vec::new(),
+        };
+
+        context.build(&options)?;
         let end = Instant::now();
         let duration = end.duration_since(start);
-        if context.quiet {
+        if context.quiet && self.emit == Emit::Rust {
+            println!(
+                "   \x1B[32m\x1b[1mGeneration Finished\x1b[0m: Rust packages created in \x1B[34m\x1b[1m'{:.2?}' \x1b[0m", duration
+            );
+        } else if context.quiet {
             println!(
                 "   \x1B[32m\x1b[1mBuild Finished\x1b[0m: Workflow created in \x1B[34m\x1b[1m'{:.2?}' \x1b[0m", duration 
             );
@@ -3,6 +3,7 @@ use clap::Parser;
 use super::*;
 
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Create {
     pub package_name: String,
 }
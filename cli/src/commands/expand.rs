@@ -0,0 +1,59 @@
+use super::*;
+
+/// Print the code generated for a workflow of the package.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Expand {
+    /// The name of the workflow to print the generated code of
+    pub workflow: String,
+
+    #[arg(
+        long,
+        help = "Expand the macros of the boilerplate, such as make_main_struct!, in the generated types.rs, by compiling the generated Rust package"
+    )]
+    pub macros: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Optional Rust package generation path, where the package is generated to expand its macros"
+    )]
+    pub build_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Expand the macros without accessing the network, from the dependencies vendored by `composer vendor`"
+    )]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        help = "Directory of the vendored dependencies, the vendor directory of the package by default"
+    )]
+    pub vendor_dir: Option<PathBuf>,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Execute<Context> for Expand {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        context.init(self.source, self.build_dir, None)?;
+        context.parse()?;
+
+        let workflow = context.workflow(Some(&self.workflow))?;
+        let options = BuildOptions {
+            offline: self.offline,
+            vendor: context.vendored_sources(self.vendor_dir)?,
+            cache: BuildOptions::default_cache(),
+            ..Default::default()
+        };
+
+        print!("{}", context.expand(&workflow, self.macros, &options)?);
+
+        Ok(())
+    }
+}
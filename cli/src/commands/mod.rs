@@ -1,16 +1,23 @@
 mod build;
 mod create;
+mod expand;
+mod run;
+mod test;
 mod validate;
+mod vendor;
 
-use self::{create::Create, validate::Validate};
-use crate::errors::io_error;
+use self::{
+    create::Create, expand::Expand, run::Run, test::Test, validate::Validate, vendor::Vendor,
+};
+use crate::errors::{io_error, IOError};
 use crate::types::Context;
 use build::Build;
 use clap::Parser;
 use composer_primitives::result;
-use composer_primitives::{Execute, Result};
+use composer_primitives::{Exception, Execute, Result};
+use echo_library::{BuildOptions, BuildTarget, Emit};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 #[derive(Parser, Debug)]
@@ -27,9 +34,35 @@ pub enum Commands {
         command: Create,
     },
 
+    #[command(about = "Print the Rust code generated for a workflow of the current package")]
+    Expand {
+        #[command(flatten)]
+        command: Expand,
+    },
+
+    #[command(about = "Build the current package and run its workflow locally")]
+    Run {
+        #[command(flatten)]
+        command: Run,
+    },
+
+    #[command(about = "Build the current package and run the test cases of its tests directory")]
+    Test {
+        #[command(flatten)]
+        command: Test,
+    },
+
     #[structopt(about = "Validate the configuration file")]
     Validate {
         #[command(flatten)]
         command: Validate,
     },
+
+    #[command(
+        about = "Vendor the dependencies of the workflows of the current package, to build them offline"
+    )]
+    Vendor {
+        #[command(flatten)]
+        command: Vendor,
+    },
 }
@@ -0,0 +1,124 @@
+use super::*;
+use echo_library::{MockResponses, WorkflowRuntime};
+
+/// Run a built workflow locally.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Run {
+    #[arg(
+        short,
+        long,
+        help = "Path to the JSON file holding the input of the workflow, an empty object by default"
+    )]
+    pub input: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        help = "Name of the workflow to run, required when the package declares several"
+    )]
+    pub workflow: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Path to the JSON file holding the outputs served to the tasks of the mock kind"
+    )]
+    pub mock: Option<PathBuf>,
+
+    #[arg(short, long, help = "Optional path to output workflow wasm")]
+    pub output: Option<PathBuf>,
+
+    /// The path of a built workflow wasm, or of the directory where the package to build is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Run {
+    /// Builds the workflow to run from the package at the source, unless the source is
+    /// already a wasm file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf>` - The path of the wasm of the workflow to run
+    ///
+    fn wasm(&self, context: &mut Context) -> Result<PathBuf> {
+        if let Some(source) = &self.source {
+            if source
+                .extension()
+                .is_some_and(|extension| extension == "wasm")
+            {
+                return Ok(source.clone());
+            }
+        }
+
+        context.init(self.source.clone(), None, self.output.clone())?;
+        context.parse()?;
+        let workflow = context.workflow(self.workflow.as_deref())?;
+        let options = BuildOptions {
+            vendor: context.vendored_sources(None)?,
+            cache: BuildOptions::default_cache(),
+            workflows: vec![workflow.clone()],
+            ..Default::default()
+        };
+        context.build(&options)?;
+
+        context.artifact_path(&workflow, &options.target)
+    }
+
+    fn input(&self) -> Result<serde_json::Value> {
+        let input = match &self.input {
+            Some(path) => fs::read_to_string(path).map_err(io_error)?,
+            None => return Ok(serde_json::json!({})),
+        };
+
+        serde_json::from_str(&input).map_err(|err| {
+            Box::new(IOError::Other(format!(
+                "Invalid input {}: {}",
+                self.input.as_ref().unwrap().display(),
+                err
+            ))) as Box<dyn Exception>
+        })
+    }
+}
+
+impl Execute<Context> for Run {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        let input = self.input()?;
+        let mocks = match &self.mock {
+            Some(path) => MockResponses::from_file(path)
+                .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?,
+            None => MockResponses::default(),
+        };
+        let wasm = self.wasm(&mut context)?;
+
+        let start = Instant::now();
+        let output = WorkflowRuntime::from_file(&wasm)
+            .and_then(|runtime| runtime.with_mocks(mocks).run(&input))
+            .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?;
+        let duration = Instant::now().duration_since(start);
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output)
+                .map_err(|err| Box::new(IOError::Other(err.to_string())) as Box<dyn Exception>)?
+        );
+
+        if output["status"] == "failure" {
+            return Err(Box::new(IOError::Other(format!(
+                "Workflow {} failed",
+                wasm.display()
+            ))));
+        }
+
+        if context.quiet {
+            eprintln!(
+                "   \x1B[32m\x1b[1mRun Finished\x1b[0m: Workflow executed in \x1B[34m\x1b[1m'{:.2?}' \x1b[0m", duration
+            );
+        }
+
+        Ok(())
+    }
+}
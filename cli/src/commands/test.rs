@@ -0,0 +1,119 @@
+use super::*;
+use echo_library::{TestCase, WorkflowRuntime};
+
+/// Build the package and run its tests.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Test {
+    #[arg(short, long, help = "Optional path to output workflow wasm")]
+    pub output: Option<PathBuf>,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Test {
+    /// Runs a test case on the wasm of its workflow
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The failures of the case, empty if the case passes
+    ///
+    fn run_case(case: &TestCase, wasm: &Path) -> Vec<String> {
+        let envelope = WorkflowRuntime::from_file(wasm)
+            .and_then(|runtime| runtime.with_mocks(case.mocks.clone()).run(&case.input));
+
+        match envelope {
+            Ok(envelope) => case.check(&envelope),
+            Err(err) => vec![format!("the workflow could not be run: {}", err)],
+        }
+    }
+}
+
+impl Execute<Context> for Test {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        let start = Instant::now();
+        context.init(self.source, None, self.output)?;
+        context.parse()?;
+
+        let mut cases = Vec::new();
+
+        for path in context.test_files()? {
+            let case = TestCase::from_file(&path)
+                .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?;
+            let workflow = context.workflow(case.workflow.as_deref())?;
+
+            cases.push((case, workflow));
+        }
+
+        if cases.is_empty() {
+            return Err(Box::new(IOError::Other(
+                "No test found, add the test cases to the tests directory of the package"
+                    .to_string(),
+            )));
+        }
+
+        // Only the workflows under test are built
+        let options = BuildOptions {
+            vendor: context.vendored_sources(None)?,
+            cache: BuildOptions::default_cache(),
+            workflows: cases.iter().map(|(_, workflow)| workflow.clone()).collect(),
+            ..Default::default()
+        };
+        context.build(&options)?;
+
+        println!("\nrunning {} tests", cases.len());
+
+        let mut failures = Vec::new();
+
+        for (case, workflow) in cases.iter() {
+            let wasm = context.artifact_path(workflow, &options.target)?;
+            let failure = Self::run_case(case, &wasm);
+
+            if failure.is_empty() {
+                println!("test {} ... \x1B[32mok\x1b[0m", case.name);
+            } else {
+                println!("test {} ... \x1B[31mFAILED\x1b[0m", case.name);
+                failures.push((case.name.clone(), failure));
+            }
+        }
+
+        if !failures.is_empty() {
+            println!("\nfailures:");
+
+            for (name, failure) in failures.iter() {
+                println!("\n---- {} ----", name);
+
+                for line in failure {
+                    println!("  {}", line);
+                }
+            }
+        }
+
+        let duration = Instant::now().duration_since(start);
+        println!(
+            "\ntest result: {}. {} passed; {} failed; finished in {:.2?}\n",
+            if failures.is_empty() {
+                "\x1B[32mok\x1b[0m"
+            } else {
+                "\x1B[31mFAILED\x1b[0m"
+            },
+            cases.len() - failures.len(),
+            failures.len(),
+            duration
+        );
+
+        if !failures.is_empty() {
+            return Err(Box::new(IOError::Other(format!(
+                "{} of {} tests failed",
+                failures.len(),
+                cases.len()
+            ))));
+        }
+
+        Ok(())
+    }
+}
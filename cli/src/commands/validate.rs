@@ -1,9 +1,9 @@
 use super::*;
 /// Compile the config file.
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Validate {
-
-    /// The path(relative path or absolute path) of the directory, where the package is located. 
+    /// The path(relative path or absolute path) of the directory, where the package is located.
     pub source: Option<PathBuf>,
 }
 
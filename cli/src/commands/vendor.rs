@@ -0,0 +1,47 @@
+use super::*;
+
+/// Vendor the dependencies of the workflows of the package.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Vendor {
+    #[arg(
+        short,
+        long,
+        help = "Optional Rust package generation path, where the packages whose dependencies are vendored are generated"
+    )]
+    pub build_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Directory to vendor the dependencies into, the vendor directory of the package by default"
+    )]
+    pub vendor_dir: Option<PathBuf>,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Execute<Context> for Vendor {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        let start = Instant::now();
+        context.init(self.source, self.build_dir, None)?;
+        context.parse()?;
+
+        let vendor_directory = context.vendor_directory(self.vendor_dir)?;
+        context.vendor(&vendor_directory)?;
+
+        let duration = Instant::now().duration_since(start);
+        if context.quiet {
+            println!(
+                "   \x1B[32m\x1b[1mVendor Finished\x1b[0m: Dependencies vendored into {} in \x1B[34m\x1b[1m'{:.2?}' \x1b[0m",
+                vendor_directory.display(),
+                duration
+            );
+        }
+
+        Ok(())
+    }
+}
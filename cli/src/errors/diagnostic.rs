@@ -0,0 +1,91 @@
+use super::*;
+use codespan_reporting::diagnostic::{Diagnostic as Report, Label};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::{self, termcolor::Buffer};
+
+/// Renders a diagnostic of the config files in the style of rustc, quoting the source
+/// it points at when the file can be read
+///
+/// # Arguments
+///
+/// * `diagnostic` - The diagnostic raised while compiling the config files
+/// * `color` - Whether the output should contain color codes
+///
+pub fn render_diagnostic(diagnostic: &Diagnostic, color: bool) -> String {
+    let mut files = SimpleFiles::new();
+    let mut labels = Vec::new();
+    let mut notes = Vec::new();
+
+    let scope = match (&diagnostic.task, &diagnostic.workflow) {
+        (Some(task), _) => format!("in task '{}'", task),
+        (None, Some(workflow)) => format!("in workflow '{}'", workflow),
+        (None, None) => String::new(),
+    };
+
+    if let Some(location) = &diagnostic.location {
+        match std::fs::read_to_string(&location.file) {
+            Ok(source) if location.end <= source.len() => {
+                let file = files.add(location.file.clone(), source);
+                labels
+                    .push(Label::primary(file, location.begin..location.end).with_message(&scope));
+            }
+            _ => notes.push(format!("at {} {}", location, scope).trim_end().to_string()),
+        }
+    } else if !scope.is_empty() {
+        notes.push(scope);
+    }
+
+    if let Some(hint) = &diagnostic.hint {
+        notes.push(format!("hint: {}", hint));
+    }
+
+    let report = Report::error()
+        .with_code(diagnostic.code.to_string())
+        .with_message(&diagnostic.message)
+        .with_labels(labels)
+        .with_notes(notes);
+
+    let mut buffer = if color {
+        Buffer::ansi()
+    } else {
+        Buffer::no_color()
+    };
+
+    if term::emit(&mut buffer, &term::Config::default(), &files, &report).is_err() {
+        return diagnostic.to_string();
+    }
+
+    String::from_utf8_lossy(buffer.as_slice())
+        .trim_end()
+        .to_string()
+}
+
+/// Renders every diagnostic of the config files, followed by the number of errors found
+///
+/// # Arguments
+///
+/// * `diagnostics` - The diagnostics raised while compiling the config files
+/// * `color` - Whether the output should contain color codes
+///
+pub fn render_diagnostics(diagnostics: &Diagnostics, color: bool) -> String {
+    let mut rendered: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| render_diagnostic(diagnostic, color))
+        .collect();
+
+    let summary = match diagnostics.len() {
+        1 => "could not compile the package due to the previous error".to_string(),
+        count => format!(
+            "could not compile the package due to {} previous errors",
+            count
+        ),
+    };
+
+    rendered.push(if color {
+        format!("\x1B[31m\x1b[1merror\x1b[0m\x1b[1m: {}\x1b[0m", summary)
+    } else {
+        format!("error: {}", summary)
+    });
+
+    rendered.join("\n\n")
+}
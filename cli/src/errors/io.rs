@@ -6,6 +6,8 @@ pub enum IOError {
     Anyhow(Error),
     Other(String),
     Std(std::io::Error),
+    Diagnostics(Diagnostics),
+    Build(BuildErrors),
 }
 
 pub fn io_error(err: std::io::Error) -> Box<dyn Exception> {
@@ -19,6 +21,38 @@ impl Exception for IOError {
             IOError::Other(_) => 2,
             IOError::Anyhow(_) => 3,
             IOError::Std(_) => 4,
+            IOError::Diagnostics(_) => 5,
+            IOError::Build(_) => 6,
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            IOError::Diagnostics(diagnostics) => {
+                render_diagnostics(diagnostics, std::io::stderr().is_terminal())
+            }
+            IOError::Build(build) if !build.diagnostics.is_empty() => format!(
+                "   \x1B[31m\x1b[1mError\x1b[0m: \x1B[34mFailed to build the following workflows: {:?} \x1b[0m\n\n{}",
+                build.errors,
+                render_diagnostics(&build.diagnostics, std::io::stderr().is_terminal())
+            ),
+            _ => format!("   \x1B[31m\x1b[1mError\x1b[0m: \x1B[34m{} \x1b[0m", self),
+        }
+    }
+}
+
+impl From<Error> for IOError {
+    /// Keeps the diagnostics raised by the config files apart, so that they are rendered
+    /// along with the source they point at
+    fn from(error: Error) -> Self {
+        let error = match error.downcast::<Diagnostics>() {
+            Ok(diagnostics) => return IOError::Diagnostics(diagnostics),
+            Err(error) => error,
+        };
+
+        match error.downcast::<BuildErrors>() {
+            Ok(build) => IOError::Build(build),
+            Err(error) => IOError::Anyhow(error),
         }
     }
 }
@@ -39,6 +73,8 @@ impl Display for IOError {
             IOError::Anyhow(error) => write!(f, "{}", error),
             IOError::Other(error) => write!(f, "{}", error),
             IOError::Std(error) => write!(f, "{}", error),
+            IOError::Diagnostics(diagnostics) => write!(f, "{}", diagnostics),
+            IOError::Build(build) => write!(f, "{}", build),
         }
     }
 }
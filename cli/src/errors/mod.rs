@@ -1,7 +1,12 @@
 mod io;
 pub use io::*;
 
+mod diagnostic;
+pub use diagnostic::*;
+
 use anyhow::Error;
 use composer_primitives::Exception;
+use echo_library::{BuildErrors, Diagnostic, Diagnostics};
 use std::fmt::Display;
+use std::io::IsTerminal;
 use thiserror::Error;
@@ -45,7 +45,7 @@ pub fn handle_error<T>(res: Result<T>) -> T {
     match res {
         Ok(t) => t,
         Err(err) => {
-            eprintln!("   \x1B[31m\x1b[1mError\x1b[0m: \x1B[34m{} \x1b[0m", err);
+            eprintln!("{}", err.render());
             exit(err.code());
         }
     }
@@ -60,7 +60,11 @@ pub fn run_with_args(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Build { command } => command.execute(context)?,
         Commands::Create { command } => command.execute()?,
+        Commands::Expand { command } => command.execute(context)?,
+        Commands::Run { command } => command.execute(context)?,
+        Commands::Test { command } => command.execute(context)?,
         Commands::Validate { command } => command.execute(context)?,
+        Commands::Vendor { command } => command.execute(context)?,
     };
 
     Ok(())
@@ -51,26 +51,158 @@ impl Context {
         Ok(())
     }
 
+    fn source_files(&self) -> Result<&SourceFiles> {
+        self.source_files.as_ref().ok_or_else(|| {
+            Box::new(IOError::Other(
+                "Source files are not initialised".to_string(),
+            )) as Box<dyn Exception>
+        })
+    }
+
     pub fn parse(&self) -> Result<()> {
-        self.parser
-            .parse(self.source_files.as_ref().ok_or_else(|| {
-                Box::new(IOError::Other("Build file is not initialised".to_string()))
-                    as Box<dyn Exception>
-            })?)?;
+        self.parser.parse(self.source_files()?)?;
         Ok(())
     }
 
-    pub fn build(&self) -> Result<()> {
-        self.parser.build(
+    /// Builds the workflows, keeping the build directory if crates are left in it, either
+    /// generated with `Emit::Rust` or failing to compile
+    pub fn build(&mut self, options: &BuildOptions) -> Result<()> {
+        let build_directory = self.build_directory.as_mut().ok_or_else(|| {
+            Box::new(IOError::Other("Build file is not initialised".to_string()))
+                as Box<dyn Exception>
+        })?;
+
+        let result = self.parser.build(
+            build_directory,
+            self.output_directory.as_ref().ok_or_else(|| {
+                Box::new(IOError::Other("output file is not initialised".to_string()))
+                    as Box<dyn Exception>
+            })?,
+            self.quiet,
+            options,
+        );
+
+        let is_used = std::fs::read_dir(&build_directory.path)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+
+        if is_used {
+            build_directory.keep();
+        }
+
+        result
+    }
+
+    pub fn expand(
+        &self,
+        workflow: &str,
+        expand_macros: bool,
+        options: &BuildOptions,
+    ) -> Result<String> {
+        self.parser.expand(
             self.build_directory.as_ref().ok_or_else(|| {
                 Box::new(IOError::Other("Build file is not initialised".to_string()))
                     as Box<dyn Exception>
             })?,
-            self.output_directory.as_ref().ok_or_else(|| {
-                Box::new(IOError::Other("output file is not initialised".to_string()))
+            workflow,
+            expand_macros,
+            self.quiet,
+            options,
+        )
+    }
+
+    pub fn vendor(&self, vendor_directory: &Path) -> Result<()> {
+        self.parser.vendor(
+            self.build_directory.as_ref().ok_or_else(|| {
+                Box::new(IOError::Other("Build file is not initialised".to_string()))
                     as Box<dyn Exception>
             })?,
+            vendor_directory,
             self.quiet,
         )
     }
+
+    /// Directory the dependencies of the package are vendored into
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory given on the command line, `vendor` in the package if none
+    ///
+    pub fn vendor_directory(&self, path: Option<PathBuf>) -> Result<PathBuf> {
+        match path {
+            Some(path) => Ok(path),
+            None => Ok(self.source_files()?.base().join(VENDOR_DIRECTORY)),
+        }
+    }
+
+    /// Vendor directory to build the package from, either the given one or the one of the
+    /// package once `composer vendor` filled it
+    pub fn vendored_sources(&self, path: Option<PathBuf>) -> Result<Option<PathBuf>> {
+        let is_given = path.is_some();
+        let directory = self.vendor_directory(path)?;
+
+        Ok((is_given || directory.join(VENDOR_CONFIG).exists()).then_some(directory))
+    }
+
+    /// Picks the workflow to run among the workflows of the parsed package
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the workflow, which can be omitted if the package declares
+    ///   a single workflow
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The `<name>_<version>` the wasm of the workflow is named after
+    ///
+    pub fn workflow(&self, name: Option<&str>) -> Result<String> {
+        let workflows = self.parser.workflows();
+
+        let selected: Vec<&(String, String)> = workflows
+            .iter()
+            .filter(|(workflow, _)| name.is_none_or(|name| name == workflow))
+            .collect();
+
+        match selected.as_slice() {
+            [(workflow, version)] => Ok(format!("{}_{}", workflow, version)),
+            _ => {
+                let names: Vec<&str> = workflows.iter().map(|(name, _)| name.as_str()).collect();
+
+                let message = match name {
+                    Some(name) => format!(
+                        "Workflow '{}' not found, expected one of: {}",
+                        name,
+                        names.join(", ")
+                    ),
+                    None if names.is_empty() => {
+                        "The package does not declare any workflow".to_string()
+                    }
+                    None => format!(
+                        "The package declares several workflows, select one with --workflow: {}",
+                        names.join(", ")
+                    ),
+                };
+
+                Err(Box::new(IOError::Other(message)))
+            }
+        }
+    }
+
+    /// Paths of the `tests/*.json` test cases of the package
+    pub fn test_files(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.source_files()?.tests().to_vec())
+    }
+
+    /// Path of the artifact built for the workflow named `<name>_<version>`
+    pub fn artifact_path(&self, workflow: &str, target: &BuildTarget) -> Result<PathBuf> {
+        let output_directory = self.output_directory.as_ref().ok_or_else(|| {
+            Box::new(IOError::Other("output file is not initialised".to_string()))
+                as Box<dyn Exception>
+        })?;
+
+        Ok(output_directory
+            .base()
+            .join("output")
+            .join(target.output_file(workflow)))
+    }
 }
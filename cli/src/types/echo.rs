@@ -2,9 +2,16 @@ use super::*;
 
 impl Parser for Composer {
     fn parse(&self, files: &SourceFiles) -> result::Result<()> {
-        match self.compile(&format!("{}.{}", ENTRY_FILE, FILE_EXTENSION), files, &mut HashMap::new()) {
+        self.load_task_kinds(files.manifests())
+            .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)?;
+
+        match self.compile(
+            &format!("{}.{}", ENTRY_FILE, FILE_EXTENSION),
+            files,
+            &mut HashMap::new(),
+        ) {
             Ok(_) => Ok(()),
-            Err(err) => Err(Box::new(IOError::Anyhow(err))),
+            Err(err) => Err(Box::new(IOError::from(err))),
         }
     }
 
@@ -13,9 +20,53 @@ impl Parser for Composer {
         build_directory: &BuildDirectory,
         output_directory: &OutputDirectory,
         quiet: bool,
+        options: &BuildOptions,
+    ) -> result::Result<()> {
+        self.build_directory(
+            &build_directory.path,
+            output_directory.base(),
+            quiet,
+            options,
+        )
+        .map_err(|error| Box::new(IOError::from(error)) as Box<dyn Exception>)?;
+        Ok(())
+    }
+
+    fn vendor(
+        &self,
+        build_directory: &BuildDirectory,
+        vendor_directory: &Path,
+        quiet: bool,
     ) -> result::Result<()> {
-        self.build_directory(&build_directory.path, output_directory.base(), quiet)
+        self.vendor_directory(&build_directory.path, vendor_directory, quiet)
             .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)?;
         Ok(())
     }
+
+    fn expand(
+        &self,
+        build_directory: &BuildDirectory,
+        workflow: &str,
+        expand_macros: bool,
+        quiet: bool,
+        options: &BuildOptions,
+    ) -> result::Result<String> {
+        self.expand_workflow(
+            &build_directory.path,
+            workflow,
+            expand_macros,
+            quiet,
+            options,
+        )
+        .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)
+    }
+
+    fn workflows(&self) -> Vec<(String, String)> {
+        self.workflows
+            .borrow()
+            .iter()
+            .filter(|workflow| !workflow.tasks.is_empty())
+            .map(|workflow| (workflow.name.clone(), workflow.version.clone()))
+            .collect()
+    }
 }
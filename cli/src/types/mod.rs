@@ -11,8 +11,11 @@ use composer_primitives::Result;
 use crate::errors::IOError;
 use crate::types::Parser;
 use composer_primitives::{
-    constant::{ENTRY_FILE, FILE_EXTENSION},
+    constant::{ENTRY_FILE, FILE_EXTENSION, VENDOR_DIRECTORY},
     result, BuildDirectory, Exception, OutputDirectory, SourceFiles,
 };
-use echo_library::Composer;
-use std::{collections::HashMap, path::PathBuf};
+use echo_library::{BuildOptions, BuildTarget, Composer, VENDOR_CONFIG};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
@@ -7,5 +7,24 @@ pub trait Parser {
         build_directory: &BuildDirectory,
         output_directory: &OutputDirectory,
         quiet: bool,
+        options: &BuildOptions,
     ) -> Result<()>;
+    /// Vendors the dependencies of the workflows compiled by `parse` into the directory
+    fn vendor(
+        &self,
+        build_directory: &BuildDirectory,
+        vendor_directory: &Path,
+        quiet: bool,
+    ) -> Result<()>;
+    /// Generated code of the workflow named `<name>_<version>`, compiled by `parse`
+    fn expand(
+        &self,
+        build_directory: &BuildDirectory,
+        workflow: &str,
+        expand_macros: bool,
+        quiet: bool,
+        options: &BuildOptions,
+    ) -> Result<String>;
+    /// Names and versions of the workflows compiled by `parse`
+    fn workflows(&self) -> Vec<(String, String)>;
 }
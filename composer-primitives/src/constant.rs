@@ -1,2 +1,8 @@
 pub static FILE_EXTENSION: &str = "echo";
 pub static ENTRY_FILE: &str = "main";
+pub static MANIFEST_FILE: &str = "composer.toml";
+pub static KINDS_DIRECTORY: &str = "kinds";
+pub static MANIFEST_EXTENSION: &str = "toml";
+pub static TESTS_DIRECTORY: &str = "tests";
+pub static TEST_EXTENSION: &str = "json";
+pub static VENDOR_DIRECTORY: &str = "vendor";
@@ -25,4 +25,14 @@ impl BuildDirectory {
             }
         })
     }
+
+    /// Keeps the directory once the build is over, when it is a temporary directory which
+    /// would otherwise be removed, so that the generated packages can be inspected
+    pub fn keep(&mut self) -> &PathBuf {
+        if let Some(temp_dir) = self.temp_dir.take() {
+            self.path = temp_dir.keep();
+        }
+
+        &self.path
+    }
 }
@@ -1,5 +1,10 @@
 pub trait Exception: std::fmt::Display + std::fmt::Debug {
     fn code(&self) -> i32;
+
+    /// Formats the error as printed to the terminal
+    fn render(&self) -> String {
+        format!("   \x1B[31m\x1b[1mError\x1b[0m: \x1B[34m{} \x1b[0m", self)
+    }
 }
 
 pub trait Execute<T> {
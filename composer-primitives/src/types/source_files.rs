@@ -1,16 +1,43 @@
 use anyhow::Error;
-use std::{env::current_dir, ffi::OsStr, fs, path::PathBuf};
+use std::{
+    env::current_dir,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use itertools::Either;
 use std::collections::HashSet;
 use walkdir::WalkDir;
 
-use crate::constant::FILE_EXTENSION;
+use crate::constant::{
+    FILE_EXTENSION, KINDS_DIRECTORY, MANIFEST_EXTENSION, MANIFEST_FILE, TESTS_DIRECTORY,
+    TEST_EXTENSION,
+};
+
+/// Manifest file of a package, declaring custom task kinds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManifestFile {
+    /// The `composer.toml` of the package, declaring the kinds under its `kinds` table
+    Package(PathBuf),
+    /// A `kinds/<name>.toml` file, declaring a single kind named after the file
+    Kind(PathBuf),
+}
+
+impl ManifestFile {
+    pub fn path(&self) -> &Path {
+        match self {
+            ManifestFile::Package(path) | ManifestFile::Kind(path) => path,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct SourceFiles {
     base: PathBuf,
     files: HashSet<PathBuf>,
+    manifests: Vec<ManifestFile>,
+    tests: Vec<PathBuf>,
 }
 
 impl SourceFiles {
@@ -30,7 +57,6 @@ impl SourceFiles {
 
         let file_paths = fs::read_dir(&base)
             .unwrap()
-            .into_iter()
             .flat_map(|item| {
                 let item = item.unwrap();
 
@@ -54,12 +80,57 @@ impl SourceFiles {
             })
             .collect::<HashSet<PathBuf>>();
 
+        let manifests = Self::manifests_in(&base)?;
+        let tests = Self::tests_in(&base)?;
+
         Ok(SourceFiles {
             base,
             files: file_paths,
+            manifests,
+            tests,
         })
     }
 
+    /// Collects the `composer.toml` manifest and the `kinds/*.toml` kind descriptors
+    /// placed next to the entry file of the package
+    fn manifests_in(base: &Path) -> Result<Vec<ManifestFile>, Error> {
+        let mut manifests = Vec::new();
+
+        let manifest = base.join(MANIFEST_FILE);
+        if manifest.is_file() {
+            manifests.push(ManifestFile::Package(manifest));
+        }
+
+        let kinds = base.join(KINDS_DIRECTORY);
+        if kinds.is_dir() {
+            let mut kind_files = fs::read_dir(kinds)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension() == Some(OsStr::new(MANIFEST_EXTENSION)))
+                .collect::<Vec<PathBuf>>();
+
+            kind_files.sort();
+            manifests.extend(kind_files.into_iter().map(ManifestFile::Kind));
+        }
+
+        Ok(manifests)
+    }
+
+    /// Collects the `tests/*.json` test cases of the package, sorted by name
+    fn tests_in(base: &Path) -> Result<Vec<PathBuf>, Error> {
+        let tests = base.join(TESTS_DIRECTORY);
+        if !tests.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut test_files = fs::read_dir(tests)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension() == Some(OsStr::new(TEST_EXTENSION)))
+            .collect::<Vec<PathBuf>>();
+
+        test_files.sort();
+        Ok(test_files)
+    }
+
     pub fn files(&self) -> &HashSet<PathBuf> {
         &self.files
     }
@@ -67,4 +138,12 @@ impl SourceFiles {
     pub fn base(&self) -> &PathBuf {
         &self.base
     }
+
+    pub fn manifests(&self) -> &[ManifestFile] {
+        &self.manifests
+    }
+
+    pub fn tests(&self) -> &[PathBuf] {
+        &self.tests
+    }
 }
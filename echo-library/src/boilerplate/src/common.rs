@@ -1,6 +1,6 @@
 #![allow(unused_imports)]
-use paste::paste;
 use super::*;
+use paste::paste;
 #[derive(Debug, Flow)]
 pub struct WorkflowGraph {
     edges: Vec<(usize, usize)>,
@@ -23,15 +23,27 @@ macro_rules! impl_execute_trait {
             paste!{
                 $( impl Execute for $struct {
                     fn execute(&mut self) -> Result<(),String>{
-        self.run()
+        if let Some(error) = self.setter_error.take() {
+            return Err(record_error(Some(&self.action_name), WorkflowErrorKind::Dependency, error));
+        }
+        let start = start_timing();
+        let result = self.run();
+        record_timing(&self.action_name, start);
+        result.map_err(|error| record_error(Some(&self.action_name), WorkflowErrorKind::Execution, error))
     }
 
     fn get_task_output(&self) -> Value {
         self.output().clone().into()
     }
 
-    fn set_output_to_task(&mut self, input: Value) {
-        self.setter(input)
+    fn set_output_to_task(&mut self, input: Value) -> Result<(),String> {
+        let start = start_timing();
+        let result = self.setter(input);
+        record_timing(&self.action_name, start);
+        result.map_err(|error| {
+            self.setter_error = Some(error.clone());
+            record_error(Some(&self.action_name), WorkflowErrorKind::Dependency, error)
+        })
     }
                 }
             )*
@@ -39,6 +51,109 @@ macro_rules! impl_execute_trait {
     };
 }
 
+/// Kind of the failure reported in the output envelope of the workflow
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowErrorKind {
+    /// The input of the workflow is not valid JSON or does not match its arguments
+    Input,
+    /// The output of an upstream task cannot be set to the input of a task
+    Dependency,
+    /// A task fails while being executed
+    Execution,
+    /// The workflow fails outside of its tasks
+    Workflow,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WorkflowError {
+    pub task: Option<String>,
+    pub kind: WorkflowErrorKind,
+    pub message: String,
+}
+
+/// Time spent by a task in its setter and its executions, in microseconds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskTiming {
+    pub task: String,
+    pub duration_us: u64,
+}
+
+/// Failure and timings recorded while the workflow runs, read back by `_start` to
+/// build the output envelope
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub error: Option<WorkflowError>,
+    pub timings: Vec<TaskTiming>,
+}
+
+thread_local! {
+    static REPORT: std::cell::RefCell<RunReport> = std::cell::RefCell::new(RunReport::default());
+}
+
+/// Records the first failure of the workflow, keeping the task and kind it was raised with
+/// when the error is propagated further as a plain message
+#[allow(dead_code, unused)]
+pub fn record_error(
+    task: Option<&str>,
+    kind: WorkflowErrorKind,
+    message: impl Into<String>,
+) -> String {
+    let message = message.into();
+
+    REPORT.with(|report| {
+        let mut report = report.borrow_mut();
+
+        if report.error.is_none() {
+            report.error = Some(WorkflowError {
+                task: task.map(|task| task.to_string()),
+                kind,
+                message: message.clone(),
+            });
+        }
+    });
+
+    message
+}
+
+/// Instant a task starts at, read from the clock of the platform
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[allow(dead_code, unused)]
+pub fn start_timing() -> Option<std::time::Instant> {
+    Some(std::time::Instant::now())
+}
+
+/// The std of wasm32-unknown-unknown has no clock, `Instant::now` panicking there, so the
+/// tasks are recorded without a duration
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[allow(dead_code, unused)]
+pub fn start_timing() -> Option<std::time::Instant> {
+    None
+}
+
+#[allow(dead_code, unused)]
+pub fn record_timing(task: &str, start: Option<std::time::Instant>) {
+    let duration_us = start
+        .map(|start| start.elapsed().as_micros() as u64)
+        .unwrap_or_default();
+
+    REPORT.with(|report| {
+        let mut report = report.borrow_mut();
+
+        match report.timings.iter_mut().find(|timing| timing.task == task) {
+            Some(timing) => timing.duration_us += duration_us,
+            None => report.timings.push(TaskTiming {
+                task: task.to_string(),
+                duration_us,
+            }),
+        }
+    });
+}
+
+pub fn take_report() -> RunReport {
+    REPORT.with(|report| std::mem::take(&mut *report.borrow_mut()))
+}
+
 #[allow(dead_code, unused)]
 pub fn join_hashmap<T: PartialEq + std::hash::Hash + Eq + Clone, U: Clone, V: Clone>(
     first: HashMap<T, U>,
@@ -55,22 +170,66 @@ pub fn join_hashmap<T: PartialEq + std::hash::Hash + Eq + Clone, U: Clone, V: Cl
     data
 }
 
-#[no_mangle]
+#[cfg_attr(not(feature = "component"), no_mangle)]
 pub unsafe extern "C" fn free_memory(ptr: *mut u8, size: u32, alignment: u32) {
     let layout = Layout::from_size_align_unchecked(size as usize, alignment as usize);
     alloc::alloc::dealloc(ptr, layout);
 }
 
+#[cfg(all(target_arch = "wasm32", not(feature = "component")))]
 #[link(wasm_import_module = "host")]
 extern "C" {
     pub fn set_output(ptr: i32, size: i32);
 }
+/// Version of the output envelope, bumped whenever its shape changes
+pub const OUTPUT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStatus {
+    Success,
+    Failure,
+}
+
+/// Envelope passed to the host through `set_output`, telling a failing workflow apart
+/// from a successful one
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Output {
+    pub version: u32,
+    pub status: OutputStatus,
     pub result: Value,
+    pub error: Option<WorkflowError>,
+    pub timings: Vec<TaskTiming>,
+}
+
+impl Output {
+    pub fn new(result: Result<Value, String>) -> Self {
+        let report = take_report();
+
+        let (status, result, error) = match result {
+            Ok(result) => (OutputStatus::Success, result, None),
+            Err(message) => {
+                let error = report.error.unwrap_or(WorkflowError {
+                    task: None,
+                    kind: WorkflowErrorKind::Workflow,
+                    message,
+                });
+
+                (OutputStatus::Failure, Value::Null, Some(error))
+            }
+        };
+
+        Output {
+            version: OUTPUT_VERSION,
+            status,
+            result,
+            error,
+            timings: report.timings,
+        }
+    }
 }
 
-#[no_mangle]
+#[cfg_attr(not(feature = "component"), no_mangle)]
 pub unsafe extern "C" fn memory_alloc(size: u32, alignment: u32) -> *mut u8 {
     let layout = Layout::from_size_align_unchecked(size as usize, alignment as usize);
     alloc::alloc::alloc(layout)
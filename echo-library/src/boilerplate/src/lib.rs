@@ -15,8 +15,13 @@ use common::*;
 use derive_enum_from_into::{EnumFrom, EnumTryInto};
 use dyn_clone::{clone_trait_object, DynClone};
 use macros::*;
-use openwhisk_rust::*;
-use paste::*;
+use openwhisk_rust::{
+    Action, Exec, HttpMethods, KeyValue, OpenWhisk, OpenwhiskClient, Rule, RuleResponse, Service,
+    Trigger, WasmClient, WskProperties,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use openwhisk_rust::NativeClient;
+use paste::paste;
 use serde::{Deserialize, Serialize};
 use serde_json::to_value;
 use serde_json::Value;
@@ -30,29 +35,61 @@ extern crate alloc;
 use codec::{Decode, Encode};
 use core::alloc::Layout;
 
+pub use common::{Output, OutputStatus};
+
+/// Runs the workflow on its JSON input, recording the failures in the output envelope
+pub fn run_workflow(input: &[u8]) -> Output {
+    let result = serde_json::from_slice(input)
+        .map_err(|err| {
+            record_error(None, WorkflowErrorKind::Input, format!("Invalid input JSON: {}", err))
+        })
+        .and_then(main);
+
+    Output::new(result)
+}
+
+/// Runs the workflow on the JSON input the host wrote into a region allocated with
+/// `memory_alloc`. The host keeps the ownership of the input region and receives the
+/// ownership of the output passed to `set_output`, releasing both with `free_memory`
+#[cfg(all(target_arch = "wasm32", not(feature = "component")))]
 #[no_mangle]
-pub fn _start(ptr: *mut u8, length: i32) {
-    let result: Value;
-    unsafe {
-        let mut vect = Vec::new();
-        for i in 1..=length {
-            if let Some(val_back) = ptr.as_ref() {
-                vect.push(val_back.clone());
+pub unsafe extern "C" fn _start(ptr: *mut u8, length: i32) {
+    let input = std::slice::from_raw_parts(ptr, length.max(0) as usize);
+
+    let output = run_workflow(input);
+    let serialized = serde_json::to_vec(&output).unwrap().into_boxed_slice();
+    let size = serialized.len() as i32;
+    let ptr = Box::into_raw(serialized) as *mut u8;
+
+    set_output(ptr as i32, size);
+}
+
+/// Exports the workflow as the `run` function of the world described by `wit/world.wit`
+#[cfg(feature = "component")]
+mod component {
+    wit_bindgen::generate!({ path: "wit" });
+
+    struct Workflow;
+
+    impl Guest for Workflow {
+        fn run(input: String) -> Result<String, WorkflowError> {
+            let output = super::run_workflow(input.as_bytes());
+
+            match output.error {
+                None => Ok(output.result.to_string()),
+                Some(error) => Err(WorkflowError {
+                    task: error.task,
+                    kind: match error.kind {
+                        super::WorkflowErrorKind::Input => ErrorKind::Input,
+                        super::WorkflowErrorKind::Dependency => ErrorKind::Dependency,
+                        super::WorkflowErrorKind::Execution => ErrorKind::Execution,
+                        super::WorkflowErrorKind::Workflow => ErrorKind::Workflow,
+                    },
+                    message: error.message,
+                }),
             }
-            *ptr = *ptr.add(i as usize);
         }
-        result = serde_json::from_slice(&vect).unwrap();
     }
 
-    let res = main(result);
-    let output = Output {
-        result: serde_json::to_value(res).unwrap(),
-    };
-    let serialized = serde_json::to_vec(&output).unwrap();
-    let size = serialized.len() as i32;
-    let ptr = serialized.as_ptr();
-    std::mem::forget(ptr);
-    unsafe {
-        set_output(ptr as i32, size);
-    }
+    export!(Workflow);
 }
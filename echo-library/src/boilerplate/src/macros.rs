@@ -10,7 +10,7 @@ macro_rules! make_input_struct {
         [$($der:ident),*]
 ) => {
         #[derive($($der),*)]
-            pub struct $x { 
+            pub struct $x {
             $(
                 $(#[serde(default=$default_derive)])?
                 $visibility  $element: $ty
@@ -36,7 +36,9 @@ macro_rules! make_main_struct {
             action_name: String,
             pub input: $input,
             pub output: Value,
-            pub mapout: Value
+            pub mapout: Value,
+            #[serde(skip)]
+            setter_error: Option<String>
         }
         impl $name{
             pub fn output(&self) -> Value {
@@ -61,7 +63,7 @@ macro_rules! impl_new {
                         ..Default::default()
                     },
                     ..Default::default()
-                }      
+                }
             }
         }
     };
@@ -79,24 +81,83 @@ macro_rules! impl_new {
                         ..Default::default()
                     },
                     ..Default::default()
-                }      
+                }
             }
         }
     }
 }
 
+#[macro_export]
+macro_rules! output_field {
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal
+    ) => {
+        serde_json::from_value($value.clone()).map_err(|err| {
+            format!(
+                "Task '{}' cannot set `{}` from the output of task '{}': {}",
+                $task,
+                stringify!($element).trim_start_matches("r#"),
+                $upstream,
+                err
+            )
+        })
+    };
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal,
+        $key:literal
+    ) => {
+        match $value.get($key) {
+            Some(field) => serde_json::from_value(field.clone()).map_err(|err| {
+                format!(
+                    "Task '{}' cannot set `{}` from the field `{}` of task '{}': {}",
+                    $task,
+                    stringify!($element).trim_start_matches("r#"),
+                    $key,
+                    $upstream,
+                    err
+                )
+            }),
+            None => Err(format!(
+                "Task '{}' cannot set `{}`: the output of task '{}' has no field `{}`",
+                $task,
+                stringify!($element).trim_start_matches("r#"),
+                $upstream,
+                $key
+            )),
+        }
+    };
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal,
+        $key:ident,
+        $output:ty
+    ) => {
+        output_field!($value, $task, $element, $upstream).map(|output: $output| output.$key)
+    };
+}
+
 #[macro_export]
 macro_rules! impl_setter {
     (
         $name:ty,
-        [$($element:ident : $key:expr),*]
+        [$($element:ident : $key:tt $(as $output:ty)? => $upstream:literal),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
+            pub fn setter(&mut self, value: Value) -> Result<(), String> {
                 $(
-                    let val = value.get($key).unwrap();
-                    self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                    self.input.$element = output_field!(
+                        value, self.action_name, $element, $upstream, $key $(, $output)?
+                    )?;
                 )*
+                Ok(())
             }
         }
     }
@@ -106,48 +167,60 @@ macro_rules! impl_setter {
 macro_rules! impl_map_setter {
     (
         $name:ty,
-        $element:ident : $key:expr,  
+        $element:ident : $key:tt $(as $input_output:ty)? => $upstream:literal,
         $typ_name : ty,
         $out:expr
     ) => {
         impl $name {
-            pub fn setter(&mut self, val: Value) {
-                
-                    let value = val.get($key).unwrap();
-                    let value = serde_json::from_value::<Vec<$typ_name>>(value.clone()).unwrap();
-                    let mut map: HashMap<_, _> = value
-                        .iter()
-                        .map(|x| {
-                            self.input.$element = x.to_owned() as $typ_name;
-                            self.run();
-                            (x.to_owned(), self.output.get($out).unwrap().to_owned())
-                        })
-                        .collect();
-                    self.mapout = to_value(map).unwrap();
-                
+            pub fn setter(&mut self, val: Value) -> Result<(), String> {
+                let value: Vec<$typ_name> = output_field!(
+                    val, self.action_name, $element, $upstream, $key $(, $input_output)?
+                )?;
+                let mut map = HashMap::new();
+
+                for x in value {
+                    self.input.$element = x.clone();
+                    self.run().map_err(|error| {
+                        record_error(Some(&self.action_name), WorkflowErrorKind::Execution, error)
+                    })?;
+
+                    let output = self.output.get($out).ok_or_else(|| {
+                        format!(
+                            "Task '{}' cannot map `{}`: its output has no field `{}`",
+                            self.action_name, stringify!($element).trim_start_matches("r#"), $out
+                        )
+                    })?;
+                    map.insert(x, output.to_owned());
+                }
+
+                self.mapout = to_value(map).map_err(|err| err.to_string())?;
+                Ok(())
             }
         }
     }
-    }
+}
 
 #[macro_export]
 macro_rules! impl_concat_setter {
     (
         $name:ty,
-        $input:ident
+        $input:ident,
+        [$first:literal, $second:literal]
     ) => {
-        impl $name{
-            pub fn setter(&mut self, val: Value) {
-                
-                    let val: Vec<Value> = serde_json::from_value(val).unwrap();
-                    let res = join_hashmap(
-                        serde_json::from_value(val[0].to_owned()).unwrap(),
-                        serde_json::from_value(val[1].to_owned()).unwrap(),
-                    );
-                    self.input.$input = res;
+        impl $name {
+            pub fn setter(&mut self, val: Value) -> Result<(), String> {
+                let val: Vec<Value> = serde_json::from_value(val).map_err(|err| err.to_string())?;
+                let first = val.get(0).cloned().unwrap_or_default();
+                let second = val.get(1).cloned().unwrap_or_default();
+
+                self.input.$input = join_hashmap(
+                    output_field!(first, self.action_name, $input, $first)?,
+                    output_field!(second, self.action_name, $input, $second)?,
+                );
+                Ok(())
             }
         }
-    }
+    };
 }
 
 #[allow(unused)]
@@ -158,22 +231,27 @@ macro_rules! impl_combine_setter {
         [$(
             $(($value_input:ident))?
             $([$index:expr])?
-            $element:ident : $key:expr),*]
+            $element:ident : $key:tt $(as $output:ty)? => $upstream:literal),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
+            pub fn setter(&mut self, value: Value) -> Result<(), String> {
 
-                let value: Vec<Value> = serde_json::from_value(value).unwrap();
+                let value: Vec<Value> = serde_json::from_value(value).map_err(|err| err.to_string())?;
                 $(
+                    let upstream_output = value.get($($index)*).cloned().unwrap_or_default();
+
                     if stringify!($($value_input)*).is_empty(){
-                        let val = value[$($index)*].get($key).unwrap();
-                        self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                        self.input.$element = output_field!(
+                            upstream_output, self.action_name, $element, $upstream, $key $(, $output)?
+                        )?;
                     }else{
-                        self.input.$element = serde_json::from_value(value[$($index)*].to_owned()).unwrap();
+                        self.input.$element = output_field!(
+                            upstream_output, self.action_name, $element, $upstream
+                        )?;
                     }
                 )*
+                Ok(())
             }
         }
     }
 }
-
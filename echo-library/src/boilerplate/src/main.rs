@@ -0,0 +1,22 @@
+use boilerplate::{run_workflow, OutputStatus};
+use std::io::Read;
+use std::process::ExitCode;
+
+/// Runs the workflow natively on the JSON input read from stdin and prints its output
+/// envelope, exiting with a failure status when the workflow fails
+fn main() -> ExitCode {
+    let mut input = Vec::new();
+
+    if let Err(err) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("Failed to read the input from stdin: {}", err);
+        return ExitCode::FAILURE;
+    }
+
+    let output = run_workflow(&input);
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+
+    match output.status {
+        OutputStatus::Success => ExitCode::SUCCESS,
+        OutputStatus::Failure => ExitCode::FAILURE,
+    }
+}
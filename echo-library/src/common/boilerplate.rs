@@ -1,7 +1,6 @@
-
 pub const COMMON: &str = r#"#![allow(unused_imports)]
-use paste::paste;
 use super::*;
+use paste::paste;
 #[derive(Debug, Flow)]
 pub struct WorkflowGraph {
     edges: Vec<(usize, usize)>,
@@ -24,15 +23,27 @@ macro_rules! impl_execute_trait {
             paste!{
                 $( impl Execute for $struct {
                     fn execute(&mut self) -> Result<(),String>{
-        self.run()
+        if let Some(error) = self.setter_error.take() {
+            return Err(record_error(Some(&self.action_name), WorkflowErrorKind::Dependency, error));
+        }
+        let start = start_timing();
+        let result = self.run();
+        record_timing(&self.action_name, start);
+        result.map_err(|error| record_error(Some(&self.action_name), WorkflowErrorKind::Execution, error))
     }
 
     fn get_task_output(&self) -> Value {
         self.output().clone().into()
     }
 
-    fn set_output_to_task(&mut self, input: Value) {
-        self.setter(input)
+    fn set_output_to_task(&mut self, input: Value) -> Result<(),String> {
+        let start = start_timing();
+        let result = self.setter(input);
+        record_timing(&self.action_name, start);
+        result.map_err(|error| {
+            self.setter_error = Some(error.clone());
+            record_error(Some(&self.action_name), WorkflowErrorKind::Dependency, error)
+        })
     }
                 }
             )*
@@ -40,6 +51,109 @@ macro_rules! impl_execute_trait {
     };
 }
 
+/// Kind of the failure reported in the output envelope of the workflow
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowErrorKind {
+    /// The input of the workflow is not valid JSON or does not match its arguments
+    Input,
+    /// The output of an upstream task cannot be set to the input of a task
+    Dependency,
+    /// A task fails while being executed
+    Execution,
+    /// The workflow fails outside of its tasks
+    Workflow,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WorkflowError {
+    pub task: Option<String>,
+    pub kind: WorkflowErrorKind,
+    pub message: String,
+}
+
+/// Time spent by a task in its setter and its executions, in microseconds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaskTiming {
+    pub task: String,
+    pub duration_us: u64,
+}
+
+/// Failure and timings recorded while the workflow runs, read back by `_start` to
+/// build the output envelope
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub error: Option<WorkflowError>,
+    pub timings: Vec<TaskTiming>,
+}
+
+thread_local! {
+    static REPORT: std::cell::RefCell<RunReport> = std::cell::RefCell::new(RunReport::default());
+}
+
+/// Records the first failure of the workflow, keeping the task and kind it was raised with
+/// when the error is propagated further as a plain message
+#[allow(dead_code, unused)]
+pub fn record_error(
+    task: Option<&str>,
+    kind: WorkflowErrorKind,
+    message: impl Into<String>,
+) -> String {
+    let message = message.into();
+
+    REPORT.with(|report| {
+        let mut report = report.borrow_mut();
+
+        if report.error.is_none() {
+            report.error = Some(WorkflowError {
+                task: task.map(|task| task.to_string()),
+                kind,
+                message: message.clone(),
+            });
+        }
+    });
+
+    message
+}
+
+/// Instant a task starts at, read from the clock of the platform
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[allow(dead_code, unused)]
+pub fn start_timing() -> Option<std::time::Instant> {
+    Some(std::time::Instant::now())
+}
+
+/// The std of wasm32-unknown-unknown has no clock, `Instant::now` panicking there, so the
+/// tasks are recorded without a duration
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[allow(dead_code, unused)]
+pub fn start_timing() -> Option<std::time::Instant> {
+    None
+}
+
+#[allow(dead_code, unused)]
+pub fn record_timing(task: &str, start: Option<std::time::Instant>) {
+    let duration_us = start
+        .map(|start| start.elapsed().as_micros() as u64)
+        .unwrap_or_default();
+
+    REPORT.with(|report| {
+        let mut report = report.borrow_mut();
+
+        match report.timings.iter_mut().find(|timing| timing.task == task) {
+            Some(timing) => timing.duration_us += duration_us,
+            None => report.timings.push(TaskTiming {
+                task: task.to_string(),
+                duration_us,
+            }),
+        }
+    });
+}
+
+pub fn take_report() -> RunReport {
+    REPORT.with(|report| std::mem::take(&mut *report.borrow_mut()))
+}
+
 #[allow(dead_code, unused)]
 pub fn join_hashmap<T: PartialEq + std::hash::Hash + Eq + Clone, U: Clone, V: Clone>(
     first: HashMap<T, U>,
@@ -56,22 +170,66 @@ pub fn join_hashmap<T: PartialEq + std::hash::Hash + Eq + Clone, U: Clone, V: Cl
     data
 }
 
-#[no_mangle]
+#[cfg_attr(not(feature = "component"), no_mangle)]
 pub unsafe extern "C" fn free_memory(ptr: *mut u8, size: u32, alignment: u32) {
     let layout = Layout::from_size_align_unchecked(size as usize, alignment as usize);
     alloc::alloc::dealloc(ptr, layout);
 }
 
+#[cfg(all(target_arch = "wasm32", not(feature = "component")))]
 #[link(wasm_import_module = "host")]
 extern "C" {
     pub fn set_output(ptr: i32, size: i32);
 }
+/// Version of the output envelope, bumped whenever its shape changes
+pub const OUTPUT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStatus {
+    Success,
+    Failure,
+}
+
+/// Envelope passed to the host through `set_output`, telling a failing workflow apart
+/// from a successful one
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Output {
+    pub version: u32,
+    pub status: OutputStatus,
     pub result: Value,
+    pub error: Option<WorkflowError>,
+    pub timings: Vec<TaskTiming>,
 }
 
-#[no_mangle]
+impl Output {
+    pub fn new(result: Result<Value, String>) -> Self {
+        let report = take_report();
+
+        let (status, result, error) = match result {
+            Ok(result) => (OutputStatus::Success, result, None),
+            Err(message) => {
+                let error = report.error.unwrap_or(WorkflowError {
+                    task: None,
+                    kind: WorkflowErrorKind::Workflow,
+                    message,
+                });
+
+                (OutputStatus::Failure, Value::Null, Some(error))
+            }
+        };
+
+        Output {
+            version: OUTPUT_VERSION,
+            status,
+            result,
+            error,
+            timings: report.timings,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "component"), no_mangle)]
 pub unsafe extern "C" fn memory_alloc(size: u32, alignment: u32) -> *mut u8 {
     let layout = Layout::from_size_align_unchecked(size as usize, alignment as usize);
     alloc::alloc::alloc(layout)
@@ -79,7 +237,7 @@ pub unsafe extern "C" fn memory_alloc(size: u32, alignment: u32) -> *mut u8 {
 
 "#;
 
-pub const LIB: &str =  r#"#![allow(unused_imports)]
+pub const LIB: &str = r#"#![allow(unused_imports)]
 #![allow(unused_macros)]
 #![allow(unused_variables)]
 #![allow(dead_code)]
@@ -96,8 +254,13 @@ use common::*;
 use derive_enum_from_into::{EnumFrom, EnumTryInto};
 use dyn_clone::{clone_trait_object, DynClone};
 use macros::*;
-use openwhisk_rust::*;
-use paste::*;
+use openwhisk_rust::{
+    Action, Exec, HttpMethods, KeyValue, OpenWhisk, OpenwhiskClient, Rule, RuleResponse, Service,
+    Trigger, WasmClient, WskProperties,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use openwhisk_rust::NativeClient;
+use paste::paste;
 use serde::{Deserialize, Serialize};
 use serde_json::to_value;
 use serde_json::Value;
@@ -111,48 +274,80 @@ extern crate alloc;
 use codec::{Decode, Encode};
 use core::alloc::Layout;
 
+pub use common::{Output, OutputStatus};
+
+/// Runs the workflow on its JSON input, recording the failures in the output envelope
+pub fn run_workflow(input: &[u8]) -> Output {
+    let result = serde_json::from_slice(input)
+        .map_err(|err| {
+            record_error(None, WorkflowErrorKind::Input, format!("Invalid input JSON: {}", err))
+        })
+        .and_then(main);
+
+    Output::new(result)
+}
+
+/// Runs the workflow on the JSON input the host wrote into a region allocated with
+/// `memory_alloc`. The host keeps the ownership of the input region and receives the
+/// ownership of the output passed to `set_output`, releasing both with `free_memory`
+#[cfg(all(target_arch = "wasm32", not(feature = "component")))]
 #[no_mangle]
-pub fn _start(ptr: *mut u8, length: i32) {
-    let result: Value;
-    unsafe {
-        let mut vect = Vec::new();
-        for i in 1..=length {
-            if let Some(val_back) = ptr.as_ref() {
-                vect.push(val_back.clone());
+pub unsafe extern "C" fn _start(ptr: *mut u8, length: i32) {
+    let input = std::slice::from_raw_parts(ptr, length.max(0) as usize);
+
+    let output = run_workflow(input);
+    let serialized = serde_json::to_vec(&output).unwrap().into_boxed_slice();
+    let size = serialized.len() as i32;
+    let ptr = Box::into_raw(serialized) as *mut u8;
+
+    set_output(ptr as i32, size);
+}
+
+/// Exports the workflow as the `run` function of the world described by `wit/world.wit`
+#[cfg(feature = "component")]
+mod component {
+    wit_bindgen::generate!({ path: "wit" });
+
+    struct Workflow;
+
+    impl Guest for Workflow {
+        fn run(input: String) -> Result<String, WorkflowError> {
+            let output = super::run_workflow(input.as_bytes());
+
+            match output.error {
+                None => Ok(output.result.to_string()),
+                Some(error) => Err(WorkflowError {
+                    task: error.task,
+                    kind: match error.kind {
+                        super::WorkflowErrorKind::Input => ErrorKind::Input,
+                        super::WorkflowErrorKind::Dependency => ErrorKind::Dependency,
+                        super::WorkflowErrorKind::Execution => ErrorKind::Execution,
+                        super::WorkflowErrorKind::Workflow => ErrorKind::Workflow,
+                    },
+                    message: error.message,
+                }),
             }
-            *ptr = *ptr.add(i as usize);
         }
-        result = serde_json::from_slice(&vect).unwrap();
     }
 
-    let res = main(result);
-    let output = Output {
-        result: serde_json::to_value(res).unwrap(),
-    };
-    let serialized = serde_json::to_vec(&output).unwrap();
-    let size = serialized.len() as i32;
-    let ptr = serialized.as_ptr();
-    std::mem::forget(ptr);
-    unsafe {
-        set_output(ptr as i32, size);
-    }
+    export!(Workflow);
 }
 
 "#;
-pub const TRAIT: &str =  r#"
+pub const TRAIT: &str = r#"
 
 use super::*;
 
-pub trait Execute : Debug + DynClone  {
-    fn execute(&mut self)-> Result<(),String>;
-    fn get_task_output(&self)->Value;
-    fn set_output_to_task(&mut self, inp: Value);
+pub trait Execute: Debug + DynClone {
+    fn execute(&mut self) -> Result<(), String>;
+    fn get_task_output(&self) -> Value;
+    fn set_output_to_task(&mut self, inp: Value) -> Result<(), String>;
 }
 
 clone_trait_object!(Execute);
 
 "#;
-pub const MACROS: &str =  r#"
+pub const MACROS: &str = r#"
 use super::*;
 
 #[macro_export]
@@ -165,7 +360,7 @@ macro_rules! make_input_struct {
         [$($der:ident),*]
 ) => {
         #[derive($($der),*)]
-            pub struct $x { 
+            pub struct $x {
             $(
                 $(#[serde(default=$default_derive)])?
                 $visibility  $element: $ty
@@ -191,7 +386,9 @@ macro_rules! make_main_struct {
             action_name: String,
             pub input: $input,
             pub output: Value,
-            pub mapout: Value
+            pub mapout: Value,
+            #[serde(skip)]
+            setter_error: Option<String>
         }
         impl $name{
             pub fn output(&self) -> Value {
@@ -216,7 +413,7 @@ macro_rules! impl_new {
                         ..Default::default()
                     },
                     ..Default::default()
-                }      
+                }
             }
         }
     };
@@ -234,24 +431,83 @@ macro_rules! impl_new {
                         ..Default::default()
                     },
                     ..Default::default()
-                }      
+                }
             }
         }
     }
 }
 
+#[macro_export]
+macro_rules! output_field {
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal
+    ) => {
+        serde_json::from_value($value.clone()).map_err(|err| {
+            format!(
+                "Task '{}' cannot set `{}` from the output of task '{}': {}",
+                $task,
+                stringify!($element).trim_start_matches("r#"),
+                $upstream,
+                err
+            )
+        })
+    };
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal,
+        $key:literal
+    ) => {
+        match $value.get($key) {
+            Some(field) => serde_json::from_value(field.clone()).map_err(|err| {
+                format!(
+                    "Task '{}' cannot set `{}` from the field `{}` of task '{}': {}",
+                    $task,
+                    stringify!($element).trim_start_matches("r#"),
+                    $key,
+                    $upstream,
+                    err
+                )
+            }),
+            None => Err(format!(
+                "Task '{}' cannot set `{}`: the output of task '{}' has no field `{}`",
+                $task,
+                stringify!($element).trim_start_matches("r#"),
+                $upstream,
+                $key
+            )),
+        }
+    };
+    (
+        $value:expr,
+        $task:expr,
+        $element:ident,
+        $upstream:literal,
+        $key:ident,
+        $output:ty
+    ) => {
+        output_field!($value, $task, $element, $upstream).map(|output: $output| output.$key)
+    };
+}
+
 #[macro_export]
 macro_rules! impl_setter {
     (
         $name:ty,
-        [$($element:ident : $key:expr),*]
+        [$($element:ident : $key:tt $(as $output:ty)? => $upstream:literal),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
+            pub fn setter(&mut self, value: Value) -> Result<(), String> {
                 $(
-                    let val = value.get($key).unwrap();
-                    self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                    self.input.$element = output_field!(
+                        value, self.action_name, $element, $upstream, $key $(, $output)?
+                    )?;
                 )*
+                Ok(())
             }
         }
     }
@@ -261,48 +517,60 @@ macro_rules! impl_setter {
 macro_rules! impl_map_setter {
     (
         $name:ty,
-        $element:ident : $key:expr,  
+        $element:ident : $key:tt $(as $input_output:ty)? => $upstream:literal,
         $typ_name : ty,
         $out:expr
     ) => {
         impl $name {
-            pub fn setter(&mut self, val: Value) {
-                
-                    let value = val.get($key).unwrap();
-                    let value = serde_json::from_value::<Vec<$typ_name>>(value.clone()).unwrap();
-                    let mut map: HashMap<_, _> = value
-                        .iter()
-                        .map(|x| {
-                            self.input.$element = x.to_owned() as $typ_name;
-                            self.run();
-                            (x.to_owned(), self.output.get($out).unwrap().to_owned())
-                        })
-                        .collect();
-                    self.mapout = to_value(map).unwrap();
-                
+            pub fn setter(&mut self, val: Value) -> Result<(), String> {
+                let value: Vec<$typ_name> = output_field!(
+                    val, self.action_name, $element, $upstream, $key $(, $input_output)?
+                )?;
+                let mut map = HashMap::new();
+
+                for x in value {
+                    self.input.$element = x.clone();
+                    self.run().map_err(|error| {
+                        record_error(Some(&self.action_name), WorkflowErrorKind::Execution, error)
+                    })?;
+
+                    let output = self.output.get($out).ok_or_else(|| {
+                        format!(
+                            "Task '{}' cannot map `{}`: its output has no field `{}`",
+                            self.action_name, stringify!($element).trim_start_matches("r#"), $out
+                        )
+                    })?;
+                    map.insert(x, output.to_owned());
+                }
+
+                self.mapout = to_value(map).map_err(|err| err.to_string())?;
+                Ok(())
             }
         }
     }
-    }
+}
 
 #[macro_export]
 macro_rules! impl_concat_setter {
     (
         $name:ty,
-        $input:ident
+        $input:ident,
+        [$first:literal, $second:literal]
     ) => {
-        impl $name{
-            pub fn setter(&mut self, val: Value) {
-                
-                    let val: Vec<Value> = serde_json::from_value(val).unwrap();
-                    let res = join_hashmap(
-                        serde_json::from_value(val[0].to_owned()).unwrap(),
-                        serde_json::from_value(val[1].to_owned()).unwrap(),
-                    );
-                    self.input.$input = res;
+        impl $name {
+            pub fn setter(&mut self, val: Value) -> Result<(), String> {
+                let val: Vec<Value> = serde_json::from_value(val).map_err(|err| err.to_string())?;
+                let first = val.get(0).cloned().unwrap_or_default();
+                let second = val.get(1).cloned().unwrap_or_default();
+
+                self.input.$input = join_hashmap(
+                    output_field!(first, self.action_name, $input, $first)?,
+                    output_field!(second, self.action_name, $input, $second)?,
+                );
+                Ok(())
             }
         }
-    }
+    };
 }
 
 #[allow(unused)]
@@ -313,20 +581,26 @@ macro_rules! impl_combine_setter {
         [$(
             $(($value_input:ident))?
             $([$index:expr])?
-            $element:ident : $key:expr),*]
+            $element:ident : $key:tt $(as $output:ty)? => $upstream:literal),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
+            pub fn setter(&mut self, value: Value) -> Result<(), String> {
 
-                let value: Vec<Value> = serde_json::from_value(value).unwrap();
+                let value: Vec<Value> = serde_json::from_value(value).map_err(|err| err.to_string())?;
                 $(
+                    let upstream_output = value.get($($index)*).cloned().unwrap_or_default();
+
                     if stringify!($($value_input)*).is_empty(){
-                        let val = value[$($index)*].get($key).unwrap();
-                        self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                        self.input.$element = output_field!(
+                            upstream_output, self.action_name, $element, $upstream, $key $(, $output)?
+                        )?;
                     }else{
-                        self.input.$element = serde_json::from_value(value[$($index)*].to_owned()).unwrap();
+                        self.input.$element = output_field!(
+                            upstream_output, self.action_name, $element, $upstream
+                        )?;
                     }
                 )*
+                Ok(())
             }
         }
     }
@@ -343,7 +617,7 @@ edition = "2018"
 
 
 [lib]
-crate-type = ["cdylib"]
+crate-type = ["cdylib", "rlib"]
 
 [profile.release]
 lto = true
@@ -355,6 +629,10 @@ debug = false
 
 [workspace]
 
+[features]
+# Builds a WebAssembly component exporting the world of `wit/world.wit`
+component = ["wit-bindgen"]
+
 [dependencies]
 derive-enum-from-into = "0.1.1"
 serde_derive = "1.0.192"
@@ -364,8 +642,33 @@ workflow_macro = "0.0.3"
 openwhisk-rust = "0.1.2"
 serde_json = { version = "1.0", features = ["raw_value"] }
 serde = { version = "1.0.192", features = ["derive"] }
+wit-bindgen = { version = "0.36", optional = true }
 codec = { package = "parity-scale-codec", features = [
     "derive",
 ], version = "3.1.5" }
 
-"#;
\ No newline at end of file
+"#;
+pub const MAIN: &str = r#"
+use boilerplate::{run_workflow, OutputStatus};
+use std::io::Read;
+use std::process::ExitCode;
+
+/// Runs the workflow natively on the JSON input read from stdin and prints its output
+/// envelope, exiting with a failure status when the workflow fails
+fn main() -> ExitCode {
+    let mut input = Vec::new();
+
+    if let Err(err) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("Failed to read the input from stdin: {}", err);
+        return ExitCode::FAILURE;
+    }
+
+    let output = run_workflow(&input);
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+
+    match output.status {
+        OutputStatus::Success => ExitCode::SUCCESS,
+        OutputStatus::Failure => ExitCode::FAILURE,
+    }
+}
+"#;
@@ -0,0 +1,330 @@
+use super::*;
+
+/// Path of the generated code of a workflow, relative to the root of its crate
+pub const GENERATED_TYPES: &str = "src/types.rs";
+
+/// Error message emitted by rustc while compiling the crate generated for a workflow, as
+/// read from the `--message-format=json` output of cargo
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilerMessage {
+    pub message: String,
+    /// The message rendered by rustc, quoting the generated code
+    pub rendered: Option<String>,
+    /// The line of the generated `src/types.rs` the error comes from, following the
+    /// macro expansions back to their invocation
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    message: Option<RustcMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcMessage {
+    message: String,
+    level: String,
+    #[serde(default)]
+    rendered: Option<String>,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: usize,
+    is_primary: bool,
+    #[serde(default)]
+    expansion: Option<Box<RustcExpansion>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcExpansion {
+    span: RustcSpan,
+}
+
+impl RustcSpan {
+    /// Finds the line of the generated code the span comes from, through the chain of
+    /// the macros expanded into it
+    fn generated_line(&self) -> Option<usize> {
+        if self.file_name.ends_with(GENERATED_TYPES) {
+            return Some(self.line_start);
+        }
+
+        self.expansion
+            .as_ref()
+            .and_then(|expansion| expansion.span.generated_line())
+    }
+}
+
+impl RustcMessage {
+    fn generated_line(&self) -> Option<usize> {
+        self.spans
+            .iter()
+            .filter(|span| span.is_primary)
+            .chain(self.spans.iter().filter(|span| !span.is_primary))
+            .find_map(RustcSpan::generated_line)
+    }
+}
+
+/// Reads the errors emitted by rustc from the `--message-format=json` output of cargo,
+/// ignoring the lines which are not JSON messages, such as the output of build scripts
+///
+/// # Arguments
+///
+/// * `output` - The standard output of `cargo build --message-format=json`
+///
+/// # Returns
+///
+/// * `Vec<CompilerMessage>` - The errors in the order they were emitted, without the
+///   summaries such as `aborting due to previous error`
+///
+pub fn parse_compiler_messages(output: &str) -> Vec<CompilerMessage> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|message| message.reason == "compiler-message")
+        .filter_map(|message| message.message)
+        .filter(|message| message.level == "error" && !message.spans.is_empty())
+        .map(|message| CompilerMessage {
+            line: message.generated_line(),
+            message: message.message,
+            rendered: message.rendered,
+        })
+        .collect()
+}
+
+/// Failure of `cargo build` on the crate generated for a workflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildFailure {
+    /// The exit status of cargo, as displayed by the process
+    pub status: String,
+    pub messages: Vec<CompilerMessage>,
+}
+
+impl Display for BuildFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cargo build failed with {}", self.status)?;
+
+        for message in self.messages.iter() {
+            write!(f, "\n{}", message.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for BuildFailure {}
+
+/// Failure of the build of the workflows of a package, keeping the diagnostics of the
+/// generated code apart from the other errors, so that they are rendered along with the
+/// tasks they come from
+#[derive(Debug)]
+pub struct BuildErrors {
+    pub errors: Vec<Error>,
+    pub diagnostics: Diagnostics,
+}
+
+impl Display for BuildErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to build the following workflows: {:?}",
+            self.errors
+        )?;
+
+        if !self.diagnostics.is_empty() {
+            write!(f, "\n\n{}", self.diagnostics)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for BuildErrors {}
+
+/// Splits a line of Rust code into the identifiers it contains
+fn identifiers(code: &str) -> impl Iterator<Item = &str> {
+    code.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|identifier| !identifier.is_empty())
+}
+
+/// Checks whether an unindented line of the generated code starts a new item, such as
+/// the invocation of a macro or a function
+fn is_item_start(line: &str) -> bool {
+    let starts_macro = line
+        .split_once("!(")
+        .is_some_and(|(name, _)| !name.is_empty() && identifiers(name).eq([name]));
+
+    starts_macro
+        || [
+            "impl ",
+            "pub fn ",
+            "fn ",
+            "#[",
+            "use ",
+            "pub struct ",
+            "struct ",
+        ]
+        .iter()
+        .any(|start| line.starts_with(start))
+}
+
+/// Finds the task and the argument of the workflow a line of the generated `types.rs`
+/// was generated for
+///
+/// The line is looked up for the names the generated code gives to the tasks, such as
+/// `<Task>Input` or `<task>_index`, and then the item holding it, such as the invocation
+/// of `make_main_struct!`. The argument is the field of the task named by the line or
+/// by the message of the error.
+///
+/// # Arguments
+///
+/// * `types_rs` - The code generated for the workflow
+/// * `line` - The line of the code, starting from 1
+/// * `message` - The message of the error raised at the line
+/// * `workflow` - The workflow the code was generated for
+///
+/// # Returns
+///
+/// * `Option<(String, Option<String>)>` - The name of the task and of its argument, if
+///   the line can be traced back to a task
+///
+pub fn locate_generated_line(
+    types_rs: &str,
+    line: usize,
+    message: &str,
+    workflow: &Workflow,
+) -> Option<(String, Option<String>)> {
+    let lines: Vec<&str> = types_rs.lines().collect();
+    let index = line.checked_sub(1).filter(|index| *index < lines.len())?;
+
+    let start = (0..=index)
+        .rev()
+        .find(|index| is_item_start(lines[*index]))
+        .unwrap_or(index);
+    let end = (index + 1..lines.len())
+        .find(|index| is_item_start(lines[*index]))
+        .unwrap_or(lines.len());
+
+    let find_task = |code: &str| {
+        identifiers(code).find_map(|identifier| {
+            workflow.tasks.iter().find_map(|(name, task)| {
+                let is_named = [name, &task.action_name].iter().any(|name| {
+                    let pascal = name.to_case(Case::Pascal);
+                    let snake = name.to_case(Case::Snake);
+
+                    identifier == pascal
+                        || identifier == format!("{}Input", pascal)
+                        || identifier == format!("{}Output", pascal)
+                        || identifier == snake
+                        || identifier == format!("{}_index", snake)
+                });
+
+                is_named.then_some(name)
+            })
+        })
+    };
+
+    let find_argument = |task: &Task, code: &str| {
+        identifiers(code).find_map(|identifier| {
+            task.input_arguments
+                .iter()
+                .find(|input| {
+                    identifier == input.name || identifier == format!("{}_fn", input.name)
+                })
+                .map(|input| input.name.clone())
+        })
+    };
+
+    let error_line = format!("{} {}", lines[index], message);
+
+    let task = match find_task(lines[index]).or_else(|| find_task(&lines[start..end].join("\n"))) {
+        Some(task) => task,
+        // The input of the workflow names the arguments of the tasks, not the tasks
+        None => {
+            return workflow.tasks.iter().find_map(|(name, task)| {
+                find_argument(task, lines[index]).map(|argument| (name.clone(), Some(argument)))
+            });
+        }
+    };
+
+    let argument = workflow
+        .tasks
+        .get(task)
+        .and_then(|task| find_argument(task, &error_line));
+
+    Some((task.clone(), argument))
+}
+
+/// Converts the errors raised while compiling the crate generated for a workflow into
+/// diagnostics pointing at the tasks they come from
+///
+/// # Arguments
+///
+/// * `failure` - The failure of cargo on the generated crate
+/// * `types_rs` - The code generated for the workflow
+/// * `workflow` - The workflow the code was generated for
+///
+pub fn compilation_diagnostics(
+    failure: &BuildFailure,
+    types_rs: &str,
+    workflow: &Workflow,
+) -> Diagnostics {
+    let workflow_name = format!("{}_{}", workflow.name, workflow.version);
+
+    let mut diagnostics: Vec<Diagnostic> = failure
+        .messages
+        .iter()
+        .map(|message| {
+            let diagnostic = Diagnostic::new(
+                DiagnosticCode::Compilation,
+                format!("the generated code fails to compile: {}", message.message),
+            )
+            .in_workflow(&workflow_name);
+
+            let line = match message.line {
+                Some(line) => line,
+                None => return diagnostic,
+            };
+
+            let diagnostic = match locate_generated_line(types_rs, line, &message.message, workflow)
+            {
+                Some((task, argument)) => {
+                    let diagnostic = diagnostic
+                        .at(workflow.tasks[&task].location.clone())
+                        .in_task(&task);
+
+                    match argument {
+                        Some(argument) => diagnostic.with_hint(format!(
+                            "check the argument '{}' of the task, used at {}:{}",
+                            argument, GENERATED_TYPES, line
+                        )),
+                        None => diagnostic,
+                    }
+                }
+                None => diagnostic,
+            };
+
+            match diagnostic.hint {
+                Some(_) => diagnostic,
+                None => diagnostic.with_hint(format!("raised at {}:{}", GENERATED_TYPES, line)),
+            }
+        })
+        .collect();
+
+    if diagnostics.is_empty() {
+        diagnostics.push(
+            Diagnostic::new(
+                DiagnosticCode::Compilation,
+                format!("the generated crate fails to build: {}", failure),
+            )
+            .in_workflow(&workflow_name),
+        );
+    }
+
+    Diagnostics(diagnostics)
+}
@@ -0,0 +1,111 @@
+use super::*;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// File of the output directory recording the workflows built into it
+pub const BUILD_MANIFEST: &str = "build-manifest.json";
+
+/// Hashes of the models the artifacts of the output directory were built from, so that
+/// the workflows which did not change since their last build are skipped
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Hash of the model of every workflow, keyed by the file name of its artifact
+    pub artifacts: IndexMap<String, String>,
+}
+
+impl BuildManifest {
+    /// Reads the manifest of the output directory, empty if it is missing or unreadable,
+    /// which only causes the workflows to be rebuilt
+    pub fn load(output_dir: &Path) -> Self {
+        fs::read_to_string(output_dir.join(BUILD_MANIFEST))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<(), Error> {
+        fs::create_dir_all(output_dir)?;
+        fs::write(
+            output_dir.join(BUILD_MANIFEST),
+            serde_json::to_string_pretty(self)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Checks whether the artifact was built from the model with the given hash, and is
+    /// still in the output directory
+    pub fn is_fresh(&self, output_dir: &Path, artifact: &str, hash: &str) -> bool {
+        self.artifacts
+            .get(artifact)
+            .is_some_and(|built| built == hash)
+            && output_dir.join(artifact).is_file()
+    }
+}
+
+/// Hashes the normalized model of a workflow, covering everything its artifact is built
+/// from: its tasks without their location in the config files, the custom types and the
+/// kinds they use, the version of the composer and the options of the build
+///
+/// # Arguments
+///
+/// * `workflow` - The workflow to hash
+/// * `custom_types` - The user-defined types created within the config files
+/// * `task_kinds` - The registry of the task kinds known to the composer
+/// * `options` - The options the workflow is built with
+///
+/// # Returns
+///
+/// * `String` - The hexadecimal SHA-256 hash of the model
+///
+pub fn workflow_hash(
+    workflow: &Workflow,
+    custom_types: &IndexMap<String, String>,
+    task_kinds: &TaskKinds,
+    options: &BuildOptions,
+) -> String {
+    let mut workflow = workflow.clone();
+    let mut used_types = IndexMap::new();
+
+    for task in workflow.tasks.values_mut() {
+        task.location = None;
+
+        let types = task
+            .input_arguments
+            .iter()
+            .map(|input| &input.input_type)
+            .chain(task.output.values());
+
+        for name in types.flat_map(|typ| typ.custom_types()) {
+            used_types.insert(name.to_string(), custom_types.get(name).cloned());
+        }
+    }
+
+    let kinds: Vec<serde_json::Value> = get_common_kind(&workflow)
+        .iter()
+        .filter_map(|kind| task_kinds.get(kind))
+        .map(|kind| {
+            serde_json::json!({
+                "name": kind.name(),
+                "derive": kind.derive(),
+                "imports": kind.imports(),
+                "dependencies": kind.dependencies(),
+                "library": kind.library(),
+            })
+        })
+        .collect();
+
+    let model = serde_json::json!({
+        "composer": env!("CARGO_PKG_VERSION"),
+        "workflow": workflow,
+        "custom_types": used_types,
+        "kinds": kinds,
+        "target": options.target.to_string(),
+        "component": options.is_component(),
+    });
+
+    Sha256::digest(model.to_string().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -1,12 +1,13 @@
 use anyhow::{anyhow, Ok};
-use composer_primitives::types::SourceFiles;
+use boilerplate::*;
+use composer_primitives::types::{ManifestFile, SourceFiles};
 use rayon::prelude::*;
+use sha2::{Digest, Sha256};
 use starlark::environment::FrozenModule;
 use starlark::eval::ReturnFileLoader;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
-use boilerplate::*;
 
 use super::*;
 
@@ -17,11 +18,28 @@ use super::*;
 // const MACROS: &str = include_str!("../boilerplate/src/macros.rs");
 // const CARGO: &str = include_str!("../boilerplate/Cargo.toml");
 
+/// File of the vendor directory holding the source replacement printed by `cargo vendor`
+pub const VENDOR_CONFIG: &str = "config.toml";
+
+/// Crates generated in the build directory to vendor their dependencies, removed when
+/// dropped so that a failing vendoring does not leave them behind
+struct GeneratedCrates(Vec<PathBuf>);
+
+impl Drop for GeneratedCrates {
+    fn drop(&mut self) {
+        for crate_dir in &self.0 {
+            let _ = fs::remove_dir_all(crate_dir);
+        }
+    }
+}
+
 #[derive(Debug, ProvidesStaticType, Default)]
 pub struct Composer {
     pub config_files: Vec<String>,
     pub workflows: RefCell<Vec<Workflow>>,
-    pub custom_types: RefCell<HashMap<String, String>>,
+    pub custom_types: RefCell<IndexMap<String, String>>,
+    pub task_kinds: RefCell<TaskKinds>,
+    pub diagnostics: RefCell<Vec<Diagnostic>>,
 }
 
 impl Composer {
@@ -43,6 +61,34 @@ impl Composer {
         self.config_files.push(config.to_string());
     }
 
+    /// Registers a custom task kind to the composer, making it available to the `kind`
+    /// argument of the tasks inside the config files
+    /// This method should be called before compiling the config files
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The task kind to be registered, replacing the kind with the same name if any
+    ///
+    pub fn add_task_kind(&mut self, kind: impl TaskKind + 'static) {
+        self.task_kinds.get_mut().register(kind);
+    }
+
+    /// Registers the custom task kinds declared by the manifests of the package
+    /// This method should be called before compiling the config files
+    ///
+    /// # Arguments
+    ///
+    /// * `manifests` - The `composer.toml` and `kinds/*.toml` manifest files
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success if every manifest is valid,
+    ///   or an error naming the manifest that cannot be read or parsed
+    ///
+    pub fn load_task_kinds(&self, manifests: &[ManifestFile]) -> Result<(), Error> {
+        self.task_kinds.borrow_mut().load(manifests)
+    }
+
     /// Adds a new workflow to the composer.
     /// This method is invoked by the workflows function inside the starlark_module.
     ///
@@ -50,7 +96,7 @@ impl Composer {
     ///
     /// * `name` - Name of the workflow to be added
     /// * `version` - Version of the workflow
-    /// * `tasks` - HashMap of tasks associated with the workflow
+    /// * `tasks` - IndexMap of tasks associated with the workflow, in declaration order
     /// * `custom_types` - Optional vector of custom types names that are created within config
     ///   for the workflow.
     ///
@@ -63,15 +109,25 @@ impl Composer {
         &self,
         name: String,
         version: String,
-        tasks: HashMap<String, Task>,
+        tasks: IndexMap<String, Task>,
     ) -> Result<(), Error> {
         for workflow in self.workflows.borrow().iter() {
             if workflow.name == name {
-                return Err(Error::msg("Workflows should not have same name"));
+                return Err(Diagnostic::new(
+                    DiagnosticCode::InvalidWorkflow,
+                    "Workflows should not have same name",
+                )
+                .in_workflow(&name)
+                .with_hint("rename one of the workflows")
+                .into());
             }
         }
         if name.is_empty() {
-            Err(Error::msg("Workflow name should not be empty"))
+            Err(Diagnostic::new(
+                DiagnosticCode::InvalidWorkflow,
+                "Workflow name should not be empty",
+            )
+            .into())
         } else {
             self.workflows.borrow_mut().push(Workflow {
                 name,
@@ -82,24 +138,104 @@ impl Composer {
         }
     }
 
-    pub fn build(verbose: bool, temp_dir: &Path) -> Result<(), Error> {
-        if verbose {
-            Command::new("rustup")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["target", "add", "wasm32-wasi"])
-                .status()?;
+    /// Builds the crate generated for a workflow with cargo
+    ///
+    /// The errors of rustc are read from the JSON messages of cargo, so that a failed
+    /// build returns a `BuildFailure` holding them, to be traced back to the config files.
+    ///
+    /// # Arguments
+    ///
+    /// * `verbose` - Whether the progress of cargo and the errors of rustc are printed
+    /// * `temp_dir` - The directory holding the generated crate
+    /// * `target_dir` - The cargo target directory the crate is built in
+    /// * `options` - The options of the build
+    ///
+    pub fn build(
+        verbose: bool,
+        temp_dir: &Path,
+        target_dir: &Path,
+        options: &BuildOptions,
+    ) -> Result<(), Error> {
+        let mut args = options.target.cargo_args();
+        args.push("--message-format=json");
 
-            Command::new("cargo")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["build", "--release", "--target", "wasm32-wasi"])
-                .status()?;
+        if options.is_component() {
+            args.extend(["--features", "component"]);
+        }
+
+        if options.offline {
+            args.push("--offline");
+        }
+
+        if verbose && !options.offline {
+            if let Some(triple) = options.target.triple() {
+                let status = Command::new("rustup")
+                    .current_dir(temp_dir.join("boilerplate"))
+                    .args(["target", "add", triple])
+                    .status()?;
+
+                if !status.success() {
+                    return Err(anyhow!("Failed to add the {} target: {}", triple, status));
+                }
+            }
         } else {
-            Command::new("cargo")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["build", "--release", "--target", "wasm32-wasi", "--quiet"])
-                .status()?;
+            args.push("--quiet");
         }
-        Ok(())
+
+        let output = Command::new("cargo")
+            .current_dir(temp_dir.join("boilerplate"))
+            .env("CARGO_TARGET_DIR", target_dir)
+            .args(args)
+            .stderr(std::process::Stdio::inherit())
+            .output()?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let messages = parse_compiler_messages(&String::from_utf8_lossy(&output.stdout));
+
+        if verbose {
+            for rendered in messages
+                .iter()
+                .filter_map(|message| message.rendered.as_ref())
+            {
+                eprint!("{}", rendered);
+            }
+        }
+
+        Err(BuildFailure {
+            status: output.status.to_string(),
+            messages,
+        }
+        .into())
+    }
+
+    /// Picks the cargo target directory of the crate generated for the workflow
+    ///
+    /// With a cache, the crates with the same dependencies share a target directory, keyed
+    /// by the hash of their manifest, so that the dependencies are only compiled once.
+    fn target_directory(
+        temp_dir: &Path,
+        workflow: &Workflow,
+        task_kinds: &TaskKinds,
+        options: &BuildOptions,
+    ) -> PathBuf {
+        let cache = match &options.cache {
+            Some(cache) => cache,
+            None => return temp_dir.join("boilerplate/target"),
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(CARGO);
+        hasher.update(generate_cargo_toml_dependencies(workflow, task_kinds));
+
+        let key: String = hasher.finalize()[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        cache.join("targets").join(key)
     }
 
     fn copy_boilerplate(
@@ -107,6 +243,8 @@ impl Composer {
         types_rs: String,
         workflow_name: String,
         workflow: &Workflow,
+        task_kinds: &TaskKinds,
+        options: &BuildOptions,
     ) -> Result<PathBuf, Error> {
         let temp_dir = temp_dir.join(workflow_name);
         let curr = temp_dir.join("boilerplate");
@@ -118,15 +256,14 @@ impl Composer {
 
         std::fs::write(temp_path, COMMON)?;
 
+        let crate_name = get_crate_name(workflow);
+
         let temp_path = src_curr.as_path().join("lib.rs");
         std::fs::write(temp_path.clone(), LIB)?;
 
-        let mut lib = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(temp_path)?;
+        let mut lib = OpenOptions::new().append(true).open(temp_path)?;
 
-        let library = get_struct_stake_ledger(workflow);
+        let library = get_kind_library_code(workflow, task_kinds);
         writeln!(lib, "{library}").expect("could not able to add struct to lib");
 
         let temp_path = src_curr.as_path().join("types.rs");
@@ -138,15 +275,50 @@ impl Composer {
         let temp_path = src_curr.as_path().join("macros.rs");
         std::fs::write(temp_path, MACROS)?;
 
-        let cargo_path = curr.join("Cargo.toml");
-        std::fs::write(cargo_path.clone(), CARGO)?;
+        // The `main` reading the input from stdin only exists natively, the wasm module
+        // being started by the host
+        if options.target == BuildTarget::Native {
+            let temp_path = src_curr.as_path().join("main.rs");
+            std::fs::write(
+                temp_path,
+                MAIN.replace("use boilerplate::", &format!("use {crate_name}::")),
+            )?;
+        }
+
+        if let Some(vendor) = &options.vendor {
+            let config = fs::read_to_string(vendor.join(VENDOR_CONFIG)).map_err(|err| {
+                anyhow!(
+                    "Failed to read the sources of the vendored dependencies in {}: {}, run `composer vendor` first",
+                    vendor.display(),
+                    err
+                )
+            })?;
 
-        let mut cargo_toml = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open(cargo_path)?;
+            std::fs::create_dir_all(curr.join(".cargo"))?;
+            std::fs::write(curr.join(".cargo/config.toml"), config)?;
+        }
+
+        if options.is_component() {
+            std::fs::create_dir_all(curr.join("wit"))?;
+            std::fs::write(
+                curr.join("wit/world.wit"),
+                generate_wit_file_code(workflow)?,
+            )?;
+        }
 
-        let dependencies = generate_cargo_toml_dependencies(workflow);
+        let cargo_path = curr.join("Cargo.toml");
+        std::fs::write(
+            cargo_path.clone(),
+            CARGO.replacen(
+                "name = \"boilerplate\"",
+                &format!("name = \"{crate_name}\""),
+                1,
+            ),
+        )?;
+
+        let mut cargo_toml = OpenOptions::new().append(true).open(cargo_path)?;
+
+        let dependencies = generate_cargo_toml_dependencies(workflow, task_kinds);
         writeln!(cargo_toml, "{dependencies}")
             .expect("could not able to add dependencies to the Cargo.toml");
 
@@ -155,11 +327,108 @@ impl Composer {
 }
 
 impl Composer {
+    /// Compiles the config files of a package, starting from the given module
+    ///
+    /// Problems that do not prevent evaluating the config files are collected while
+    /// evaluating them, then every workflow is validated, so that all of them are
+    /// reported at once.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FrozenModule, Error>` - The evaluated module, or a `Diagnostics` error
+    ///   holding every problem found in the package
+    ///
     pub fn compile(
         &self,
         module: &str,
         files: &SourceFiles,
         loader: &mut HashMap<String, FrozenModule>,
+    ) -> Result<FrozenModule, Error> {
+        let result = self.evaluate(module, files, loader);
+        let mut diagnostics = self.diagnostics.take();
+
+        let module = match result {
+            Result::Ok(module) => {
+                diagnostics.extend(self.validate());
+                Some(module)
+            }
+            Err(err) => {
+                diagnostics.push(err.downcast::<Diagnostic>().unwrap_or_else(|err| {
+                    Diagnostic::new(DiagnosticCode::Evaluation, err.to_string())
+                }));
+                None
+            }
+        };
+
+        match module {
+            Some(module) if diagnostics.is_empty() => Ok(module),
+            _ => Err(Diagnostics(diagnostics).into()),
+        }
+    }
+
+    /// Records a problem found while evaluating the config files, letting the evaluation
+    /// continue so that the following problems are reported as well
+    pub fn report(&self, diagnostic: Diagnostic) {
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
+
+    /// Validates the workflows added by the config files
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Diagnostic>` - Every problem found in the workflows, empty if they are valid
+    ///
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let workflows = self.workflows.borrow();
+        let custom_types = self.custom_types.borrow();
+
+        if workflows.is_empty() {
+            return vec![Diagnostic::new(
+                DiagnosticCode::InvalidWorkflow,
+                "Empty workflow detected!!!",
+            )
+            .with_hint("declare a workflow with `workflows(name = ..., tasks = [...])`")];
+        }
+
+        let mut diagnostics = Vec::new();
+
+        for workflow in workflows.iter() {
+            diagnostics.extend(workflow.validate());
+
+            for task in workflow.tasks.values() {
+                for input in task.input_arguments.iter() {
+                    for name in input.input_type.custom_types() {
+                        if !custom_types.contains_key(name) {
+                            diagnostics.push(
+                                Diagnostic::new(
+                                    DiagnosticCode::MissingCustomType,
+                                    format!(
+                                        "Missing custom type `{}` of the argument `{}`",
+                                        name, input.name
+                                    ),
+                                )
+                                .at(task.location.clone())
+                                .in_workflow(&workflow.name)
+                                .in_task(&task.action_name)
+                                .with_hint(format!(
+                                    "declare the type with `EchoStruct(name = \"{}\", ...)`",
+                                    name
+                                )),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    fn evaluate(
+        &self,
+        module: &str,
+        files: &SourceFiles,
+        loader: &mut HashMap<String, FrozenModule>,
     ) -> Result<FrozenModule, Error> {
         let ast: AstModule = AstModule::parse_file(
             files
@@ -170,19 +439,21 @@ impl Composer {
                     module
                 )))
                 .ok_or_else(|| {
-                    Error::msg(format!(
-                        "FileNotFound at {}/{}",
-                        files.base().display(),
-                        module
-                    ))
+                    Diagnostic::new(
+                        DiagnosticCode::FileNotFound,
+                        format!("FileNotFound at {}/{}", files.base().display(), module),
+                    )
                 })?,
             &Dialect::Extended,
         )
-        .map_err(|err| Error::msg(format!("Error parsing file: {}", err)))?;
+        .map_err(|err| Diagnostic {
+            code: DiagnosticCode::Syntax,
+            ..Diagnostic::from(err)
+        })?;
 
         for load in ast.loads() {
             if loader.get(load.module_id).is_none() {
-                let frozen_module = Self::compile(self, load.module_id, files, loader)?;
+                let frozen_module = Self::evaluate(self, load.module_id, files, loader)?;
                 loader.insert(load.module_id.to_owned(), frozen_module);
             };
         }
@@ -222,12 +493,9 @@ impl Composer {
                 eval.eval_module(ast, &globals)
             };
 
-            result.map_err(|err| Error::msg(format!("Evaluation error: {}", err)))?;
+            result.map_err(Diagnostic::from)?;
         }
 
-        if self.workflows.borrow().is_empty() {
-            return Err(Error::msg("Empty workflow detected!!!"));
-        }
         Ok(module.freeze()?)
     }
 
@@ -236,77 +504,377 @@ impl Composer {
         build_path: &Path,
         out_path: &Path,
         quiet: bool,
+        options: &BuildOptions,
     ) -> anyhow::Result<(), Error> {
+        options.validate(&self.workflows.borrow(), &self.task_kinds.borrow())?;
+
         let composer_custom_types = self.custom_types.take();
+        let task_kinds: &TaskKinds = &self.task_kinds.borrow();
 
         let workflows = self.workflows.take();
 
-        let results: Vec<Result<(), Error>> = workflows
+        let output_dir = out_path.join("output");
+        let mut manifest = BuildManifest::load(&output_dir);
+
+        let results: Vec<Result<Option<(String, String)>, Error>> = workflows
             .par_iter()
             .enumerate()
             .map(|workflow: (usize, &Workflow)| {
-                if workflow.1.tasks.is_empty() {
-                    return Ok(());
+                if workflow.1.tasks.is_empty() || !options.selects(workflow.1) {
+                    return Ok(None);
                 }
 
                 let workflow_name = format!("{}_{}", workflow.1.name, workflow.1.version);
+                let artifact = options.target.output_file(&workflow_name);
+                let hash = workflow_hash(workflow.1, &composer_custom_types, task_kinds, options);
 
-                let types_rs =
-                    generate_types_rs_file_code(&workflows[workflow.0], &composer_custom_types)
-                        .map_err(|err| {
-                            anyhow!(
-                                "{}: Failed to generate types.rs file: {}",
-                                workflow.1.name,
-                                err
-                            )
-                        })?;
-
-                let temp_dir =
-                    Self::copy_boilerplate(build_path, types_rs, workflow_name.clone(), workflow.1)
-                        .map_err(|err| {
-                            anyhow!("{}: Failed to copy boilerplate: {}", workflow.1.name, err)
-                        })?;
-
-                Self::build(quiet, &temp_dir)
-                    .map_err(|err| anyhow!("{}: Failed to build: {}", workflow.1.name, err))?;
-
-                let wasm_path = format!(
-                    "{}/boilerplate/target/wasm32-wasi/release/boilerplate.wasm",
-                    temp_dir.display()
-                );
-
-                fs::create_dir_all(out_path.join("output")).map_err(|err| {
+                let is_compiled = options.emit == Emit::Artifact;
+
+                if is_compiled && !options.force && manifest.is_fresh(&output_dir, &artifact, &hash)
+                {
+                    if quiet {
+                        eprintln!("   \x1B[32m\x1b[1mFresh\x1b[0m: {}", workflow_name);
+                    }
+
+                    return Ok(None);
+                }
+
+                let types_rs = generate_types_rs_file_code(
+                    &workflows[workflow.0],
+                    &composer_custom_types,
+                    task_kinds,
+                )
+                .map_err(|err| {
                     anyhow!(
-                        "{}: Failed to create output directory: {}",
+                        "{}: Failed to generate types.rs file: {}",
                         workflow.1.name,
                         err
                     )
                 })?;
 
-                fs::copy(
-                    wasm_path,
-                    out_path.join(format!("output/{workflow_name}.wasm")),
+                let temp_dir = Self::copy_boilerplate(
+                    build_path,
+                    types_rs.clone(),
+                    workflow_name.clone(),
+                    workflow.1,
+                    task_kinds,
+                    options,
                 )
-                .map_err(|err| anyhow!("{}: Failed to copy wasm: {}", workflow.1.name, err))?;
+                .map_err(|err| {
+                    anyhow!("{}: Failed to copy boilerplate: {}", workflow.1.name, err)
+                })?;
+
+                let crate_dir = temp_dir.join("boilerplate");
+
+                if !is_compiled {
+                    eprintln!(
+                        "   \x1B[32m\x1b[1mGenerated\x1b[0m: {} in {}",
+                        workflow_name,
+                        crate_dir.display()
+                    );
+
+                    return Ok(None);
+                }
+
+                let target_dir = Self::target_directory(&temp_dir, workflow.1, task_kinds, options);
+
+                if let Err(err) = Self::build(quiet, &temp_dir, &target_dir, options) {
+                    // The crate is kept to be inspected, and built by hand once fixed
+                    eprintln!(
+                        "   \x1B[33m\x1b[1mKept\x1b[0m: the generated crate of {} in {}",
+                        workflow_name,
+                        crate_dir.display()
+                    );
+
+                    return Err(match err.downcast::<BuildFailure>() {
+                        Result::Ok(failure) => {
+                            compilation_diagnostics(&failure, &types_rs, workflow.1).into()
+                        }
+                        Err(err) => anyhow!("{}: Failed to build: {}", workflow.1.name, err),
+                    });
+                }
+
+                let artifact_path = options
+                    .target
+                    .artifact(&target_dir, &get_crate_name(workflow.1));
+
+                fs::create_dir_all(&output_dir).map_err(|err| {
+                    anyhow!(
+                        "{}: Failed to create output directory: {}",
+                        workflow.1.name,
+                        err
+                    )
+                })?;
+
+                fs::copy(artifact_path, output_dir.join(&artifact)).map_err(|err| {
+                    anyhow!(
+                        "{}: Failed to copy {}: {}",
+                        workflow.1.name,
+                        options.target,
+                        err
+                    )
+                })?;
 
                 fs::remove_dir_all(temp_dir).map_err(|err| {
                     anyhow!("{}: Failed to remove temp dir: {}", workflow.1.name, err)
                 })?;
 
-                Ok(())
+                Ok(Some((artifact, hash)))
             })
-            .filter(|result| result.is_err())
-            .collect::<Vec<_>>()
-            .into_iter()
             .collect();
 
-        if !results.is_empty() {
+        let mut errors = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut is_built = false;
+
+        for result in results {
+            match result {
+                Result::Ok(Some((artifact, hash))) => {
+                    manifest.artifacts.insert(artifact, hash);
+                    is_built = true;
+                }
+                Result::Ok(None) => {}
+                Err(err) => match err.downcast::<Diagnostics>() {
+                    Result::Ok(workflow_diagnostics) => diagnostics.extend(workflow_diagnostics.0),
+                    Err(err) => errors.push(err),
+                },
+            }
+        }
+
+        if is_built {
+            manifest.save(&output_dir)?;
+        }
+
+        // The errors of the generated code are reported along with the tasks they come from
+        if errors.is_empty() && !diagnostics.is_empty() {
+            return Err(Diagnostics(diagnostics).into());
+        }
+
+        if !errors.is_empty() {
+            return Err(BuildErrors {
+                errors,
+                diagnostics: Diagnostics(diagnostics),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Composer {
+    /// Vendors the dependencies of the crates generated for the workflows
+    ///
+    /// The crates are generated in the build directory, so that `cargo vendor` fetches the
+    /// dependencies of every kind they use. The source replacement it prints is saved next
+    /// to the vendored crates, to be copied into the crates built with these dependencies.
+    ///
+    /// # Arguments
+    ///
+    /// * `build_path` - The directory the crates are generated in, removed once vendored
+    /// * `vendor_path` - The directory the dependencies are vendored into
+    /// * `verbose` - Whether the output of cargo is shown
+    ///
+    pub fn vendor_directory(
+        &self,
+        build_path: &Path,
+        vendor_path: &Path,
+        verbose: bool,
+    ) -> Result<(), Error> {
+        let custom_types = self.custom_types.borrow();
+        let task_kinds: &TaskKinds = &self.task_kinds.borrow();
+
+        let mut crates = GeneratedCrates(Vec::new());
+
+        for workflow in self.workflows.borrow().iter() {
+            if workflow.tasks.is_empty() {
+                continue;
+            }
+
+            let types_rs = generate_types_rs_file_code(workflow, &custom_types, task_kinds)
+                .map_err(|err| {
+                    anyhow!(
+                        "{}: Failed to generate types.rs file: {}",
+                        workflow.name,
+                        err
+                    )
+                })?;
+
+            let workflow_name = format!("{}_{}", workflow.name, workflow.version);
+
+            // Removed along with the others when the boilerplate is partly copied
+            crates.0.push(build_path.join(&workflow_name));
+
+            Self::copy_boilerplate(
+                build_path,
+                types_rs,
+                workflow_name,
+                workflow,
+                task_kinds,
+                &BuildOptions::default(),
+            )
+            .map_err(|err| anyhow!("{}: Failed to copy boilerplate: {}", workflow.name, err))?;
+        }
+
+        let (first, others) = crates
+            .0
+            .split_first()
+            .ok_or_else(|| Error::msg("The package does not declare any workflow to vendor"))?;
+
+        fs::create_dir_all(vendor_path)?;
+        let vendor_path = vendor_path.canonicalize()?;
+
+        let mut command = Command::new("cargo");
+        command
+            .arg("vendor")
+            .arg("--manifest-path")
+            .arg(first.join("boilerplate/Cargo.toml"));
+
+        for other in others {
+            command
+                .arg("--sync")
+                .arg(other.join("boilerplate/Cargo.toml"));
+        }
+
+        if !verbose {
+            command.arg("--quiet");
+        }
+
+        let output = command
+            .arg(&vendor_path)
+            .stderr(std::process::Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
             return Err(Error::msg(format!(
-                "Failed to build the following workflows: {:?}",
-                results
+                "Failed to vendor the dependencies: cargo vendor exited with {}",
+                output.status
             )));
         }
 
+        fs::write(vendor_path.join(VENDOR_CONFIG), output.stdout)?;
+
+        for temp_dir in crates.0.drain(..) {
+            fs::remove_dir_all(&temp_dir).map_err(|err| {
+                anyhow!("Failed to remove temp dir {}: {}", temp_dir.display(), err)
+            })?;
+        }
+
         Ok(())
     }
 }
+
+impl Composer {
+    /// Prints the code generated for a workflow, for the review of what the config files
+    /// turn into: the dependencies appended to the `Cargo.toml` of its crate, the code of
+    /// the kinds appended to its `lib.rs`, and its `types.rs` formatted with rustfmt
+    ///
+    /// With `expand_macros`, the crate is generated in the build directory and expanded by
+    /// `cargo rustc -- -Zunpretty=expanded`, so that `types.rs` shows the code of the
+    /// macros of the boilerplate, without the implementations derived by the standard
+    /// library and serde.
+    ///
+    /// # Arguments
+    ///
+    /// * `build_path` - The directory the crate is generated in to expand its macros
+    /// * `workflow_name` - The `<name>_<version>` of the workflow
+    /// * `expand_macros` - Whether the macros are expanded in `types.rs`
+    /// * `verbose` - Whether the progress of cargo is printed
+    /// * `options` - The options the crate is built with to expand its macros
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The generated code, every file introduced by a comment naming it
+    ///
+    pub fn expand_workflow(
+        &self,
+        build_path: &Path,
+        workflow_name: &str,
+        expand_macros: bool,
+        verbose: bool,
+        options: &BuildOptions,
+    ) -> Result<String, Error> {
+        let custom_types = self.custom_types.borrow();
+        let task_kinds: &TaskKinds = &self.task_kinds.borrow();
+        let workflows = self.workflows.borrow();
+
+        let workflow = workflows
+            .iter()
+            .find(|workflow| format!("{}_{}", workflow.name, workflow.version) == workflow_name)
+            .ok_or_else(|| anyhow!("Workflow '{}' not found", workflow_name))?;
+
+        let types_rs =
+            generate_types_rs_file_code(workflow, &custom_types, task_kinds).map_err(|err| {
+                anyhow!(
+                    "{}: Failed to generate types.rs file: {}",
+                    workflow.name,
+                    err
+                )
+            })?;
+
+        let types_rs = if expand_macros {
+            let temp_dir = Self::copy_boilerplate(
+                build_path,
+                types_rs,
+                workflow_name.to_string(),
+                workflow,
+                task_kinds,
+                options,
+            )
+            .map_err(|err| anyhow!("{}: Failed to copy boilerplate: {}", workflow.name, err))?;
+
+            let target_dir = Self::target_directory(&temp_dir, workflow, task_kinds, options);
+
+            let mut command = Command::new("cargo");
+            command
+                .current_dir(temp_dir.join("boilerplate"))
+                // Unstable options of rustc are allowed on the stable toolchain
+                .env("RUSTC_BOOTSTRAP", "1")
+                .env("CARGO_TARGET_DIR", target_dir)
+                .args(["rustc", "--lib", "--profile=check"]);
+
+            if options.offline {
+                command.arg("--offline");
+            }
+
+            if !verbose {
+                command.arg("--quiet");
+            }
+
+            let output = command
+                .args(["--", "-Zunpretty=expanded"])
+                .stderr(std::process::Stdio::inherit())
+                .output()?;
+
+            fs::remove_dir_all(&temp_dir)
+                .map_err(|err| anyhow!("{}: Failed to remove temp dir: {}", workflow.name, err))?;
+
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "{}: Failed to expand the macros: cargo rustc exited with {}",
+                    workflow.name,
+                    output.status
+                ));
+            }
+
+            expanded_module(&String::from_utf8_lossy(&output.stdout), "types")?
+        } else {
+            format_rust(&types_rs)
+        };
+
+        let mut sections = vec![format!(
+            "// Cargo.toml [dependencies]\n{}",
+            generate_cargo_toml_dependencies(workflow, task_kinds)
+        )];
+
+        let library = get_kind_library_code(workflow, task_kinds);
+
+        if !library.trim().is_empty() {
+            sections.push(format!("// src/lib.rs\n{}", format_rust(&library)));
+        }
+
+        sections.push(format!("// src/types.rs\n{}", types_rs));
+
+        Ok(sections.join("\n"))
+    }
+}
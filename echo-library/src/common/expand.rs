@@ -0,0 +1,83 @@
+use super::*;
+use std::io::Write;
+use std::process::Stdio;
+
+/// Formats Rust code with rustfmt, returning it unchanged if rustfmt is not installed or
+/// fails on the code, the invocations of the macros it cannot parse being left as they are
+///
+/// # Arguments
+///
+/// * `code` - The Rust code to format
+///
+pub fn format_rust(code: &str) -> String {
+    let rustfmt = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+
+    let mut rustfmt = match rustfmt {
+        Ok(rustfmt) => rustfmt,
+        Err(_) => return code.to_string(),
+    };
+
+    if let Some(mut stdin) = rustfmt.stdin.take() {
+        if stdin.write_all(code.as_bytes()).is_err() {
+            return code.to_string();
+        }
+    }
+
+    match rustfmt.wait_with_output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).to_string()
+        }
+        _ => code.to_string(),
+    }
+}
+
+/// Extracts a module of a crate expanded by `rustc -Zunpretty=expanded`, without the
+/// implementations generated by the derive macros of the standard library and serde,
+/// which would bury the code expanded from the macros of the composer
+///
+/// # Arguments
+///
+/// * `expanded` - The source of the expanded crate
+/// * `module` - The name of the module to extract, such as `types`
+///
+/// # Returns
+///
+/// * `String` - The pretty-printed items of the module
+///
+pub fn expanded_module(expanded: &str, module: &str) -> Result<String, Error> {
+    let file = syn::parse_file(expanded)
+        .map_err(|err| Error::msg(format!("Failed to parse the expanded crate: {}", err)))?;
+
+    let items = file
+        .items
+        .into_iter()
+        .find_map(|item| match item {
+            syn::Item::Mod(item) if item.ident == module => item.content.map(|(_, items)| items),
+            _ => None,
+        })
+        .ok_or_else(|| Error::msg(format!("The expanded crate has no module {}", module)))?;
+
+    let items = items
+        .into_iter()
+        .filter(|item| match item {
+            syn::Item::Impl(item) => !item
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("automatically_derived")),
+            // Serde wraps its implementations in anonymous constants
+            syn::Item::Const(item) => item.ident != "_",
+            _ => true,
+        })
+        .collect();
+
+    Ok(prettyplease::unparse(&syn::File {
+        shebang: None,
+        attrs: Vec::new(),
+        items,
+    }))
+}
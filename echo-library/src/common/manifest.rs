@@ -0,0 +1,167 @@
+use super::*;
+use composer_primitives::types::ManifestFile;
+
+/// Attribute of a kind declared inside a manifest
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManifestAttribute {
+    pub required: bool,
+    pub format: AttributeFormat,
+    /// Values accepted by the attribute, taking precedence over `format`
+    pub values: Vec<String>,
+}
+
+/// Task kind declared inside a `composer.toml` or `kinds/<name>.toml` manifest, whose
+/// imports name the items they bring into scope of the generated code, glob imports
+/// being rejected
+///
+/// ```toml
+/// [kinds.custom_runtime]
+/// derive = "CustomRuntime"
+/// imports = ["use custom_runtime_macro::CustomRuntime;"]
+///
+/// [kinds.custom_runtime.dependencies]
+/// custom_runtime_macro = "0.1.0"
+///
+/// [kinds.custom_runtime.attributes]
+/// endpoint = { required = true, format = "url" }
+/// region = { values = ["eu", "us"] }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestKind {
+    #[serde(skip)]
+    pub name: String,
+    pub derive: String,
+    #[serde(default)]
+    pub imports: Vec<String>,
+    #[serde(default)]
+    pub dependencies: IndexMap<String, toml::Value>,
+    #[serde(default)]
+    pub attributes: IndexMap<String, ManifestAttribute>,
+    #[serde(default)]
+    pub library: String,
+}
+
+impl TaskKind for ManifestKind {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn derive(&self) -> &str {
+        &self.derive
+    }
+
+    fn imports(&self) -> Vec<String> {
+        self.imports.clone()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        self.dependencies
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect()
+    }
+
+    fn attributes(&self) -> Vec<AttributeSchema> {
+        self.attributes
+            .iter()
+            .map(|(name, attribute)| AttributeSchema {
+                name: name.clone(),
+                required: attribute.required,
+                format: if attribute.values.is_empty() {
+                    attribute.format.clone()
+                } else {
+                    AttributeFormat::Enum(attribute.values.clone())
+                },
+            })
+            .collect()
+    }
+
+    fn library(&self) -> String {
+        self.library.clone()
+    }
+}
+
+/// Project manifest placed next to the entry file of the package
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub kinds: IndexMap<String, ManifestKind>,
+}
+
+impl Manifest {
+    /// Reads the task kinds declared by a manifest file
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - Either the `composer.toml` of the package, declaring the kinds under
+    ///   its `kinds` table, or a `kinds/<name>.toml` file declaring a single kind named
+    ///   after the file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ManifestKind>, Error>` - The kinds in declaration order, or an error
+    ///   naming the manifest if it cannot be read or parsed
+    ///
+    pub fn read_kinds(manifest: &ManifestFile) -> Result<Vec<ManifestKind>, Error> {
+        let path = manifest.path();
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::msg(format!("Failed to read {}: {}", path.display(), err)))?;
+
+        let kinds = if let ManifestFile::Kind(_) = manifest {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let kind: ManifestKind = toml::from_str(&content).map_err(|err| {
+                Error::msg(format!("Invalid kind in {}: {}", path.display(), err))
+            })?;
+
+            vec![ManifestKind { name, ..kind }]
+        } else {
+            let manifest: Manifest = toml::from_str(&content).map_err(|err| {
+                Error::msg(format!("Invalid manifest {}: {}", path.display(), err))
+            })?;
+
+            manifest
+                .kinds
+                .into_iter()
+                .map(|(name, kind)| ManifestKind { name, ..kind })
+                .collect()
+        };
+
+        for kind in kinds.iter() {
+            if kind.name.is_empty() || kind.derive.is_empty() {
+                return Err(Error::msg(format!(
+                    "Kind `{}` in {} must declare a name and a derive macro",
+                    kind.name,
+                    path.display()
+                )));
+            }
+        }
+
+        Ok(kinds)
+    }
+}
+
+impl TaskKinds {
+    /// Registers the task kinds declared by the manifests of the package, the kinds
+    /// declared by a later manifest replacing the kinds with the same name
+    ///
+    /// # Arguments
+    ///
+    /// * `manifests` - The manifest files, as collected by `SourceFiles`
+    ///
+    pub fn load(&mut self, manifests: &[ManifestFile]) -> Result<(), Error> {
+        for manifest in manifests {
+            for kind in Manifest::read_kinds(manifest)? {
+                self.register(kind);
+            }
+        }
+
+        Ok(())
+    }
+}
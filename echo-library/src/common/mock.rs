@@ -0,0 +1,70 @@
+use super::*;
+use std::path::Path;
+
+include!("mock_input.rs");
+
+/// Output mocked for an action, served when the input of the task contains every field
+/// of the given input
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockResponse {
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+    pub output: serde_json::Value,
+}
+
+impl MockResponse {
+    fn matches(&self, input: &serde_json::Value) -> bool {
+        mock_input_matches(self.input.as_ref(), input)
+    }
+}
+
+/// Responses served to the tasks of the `mock` kind, keyed by action name
+///
+/// ```json
+/// {
+///   "employee_ids": [{ "input": { "role": "developer" }, "output": { "ids": [1, 2] } }],
+///   "getsalaries": [{ "input": { "id": 1 }, "output": { "salary": 1000 } }]
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct MockResponses(pub IndexMap<String, Vec<MockResponse>>);
+
+impl MockResponses {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::msg(format!("Failed to read {}: {}", path.display(), err)))?;
+
+        serde_json::from_str(&content).map_err(|err| {
+            Error::msg(format!(
+                "Invalid mock responses {}: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    /// Finds the first response of the action matching the input of the task
+    ///
+    /// # Arguments
+    ///
+    /// * `action_name` - The action name of the task
+    /// * `input` - The input of the task, holding all of its fields
+    ///
+    pub fn response(
+        &self,
+        action_name: &str,
+        input: &serde_json::Value,
+    ) -> Result<&serde_json::Value, String> {
+        self.0
+            .get(action_name)
+            .and_then(|responses| responses.iter().find(|response| response.matches(input)))
+            .map(|response| &response.output)
+            .ok_or_else(|| {
+                format!(
+                    "No mock response for the action '{}' with the input {}",
+                    action_name, input
+                )
+            })
+    }
+}
@@ -0,0 +1,15 @@
+/// Whether a mocked response applies to the input of a task. A response without input
+/// matches any input, an object matches the inputs holding each of its fields, and any
+/// other value matches an equal input.
+///
+/// Shared by `MockResponses` and the code generated for the `mock` kind, so it only uses
+/// items in scope of both.
+fn mock_input_matches(expected: Option<&serde_json::Value>, input: &serde_json::Value) -> bool {
+    match (expected, input) {
+        (None, _) => true,
+        (Some(serde_json::Value::Object(expected)), serde_json::Value::Object(input)) => expected
+            .iter()
+            .all(|(key, value)| input.get(key) == Some(value)),
+        (Some(expected), input) => expected == input,
+    }
+}
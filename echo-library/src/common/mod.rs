@@ -1,10 +1,28 @@
 pub use super::*;
 
+pub mod boilerplate;
+pub mod build_error;
+pub mod build_manifest;
 pub mod composer;
+pub mod expand;
+pub mod manifest;
+pub mod mock;
 pub mod parse_module;
+#[cfg(feature = "runtime")]
+pub mod runtime;
 pub mod starlark_modules;
-pub mod boilerplate;
+pub mod task_kind;
+pub mod test_case;
 
+pub use build_error::*;
+pub use build_manifest::*;
 pub use composer::*;
+pub use expand::*;
+pub use manifest::*;
+pub use mock::*;
 pub use parse_module::*;
+#[cfg(feature = "runtime")]
+pub use runtime::*;
 pub use starlark_modules::*;
+pub use task_kind::*;
+pub use test_case::*;
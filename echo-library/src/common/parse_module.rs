@@ -1,86 +1,342 @@
 use super::*;
+use boilerplate::{COMMON, LIB, TRAIT};
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Ident, LitStr};
 
 /// Validates the kind name of the task and returns the formatted kind if valid
 ///
 /// # Arguments
 ///
+/// * `task_kinds` - A reference to the registry of the task kinds known to the composer
 /// * `kind` - A reference to the kind name of the task
 ///
 /// # Returns
 ///
-/// * An Ok Result containing the formatted kind if the input is valid
+/// * An Ok Result containing the derive macro of the kind if the input is valid
 /// * An Err Result with an ErrorKind::NotFound if the input is not valid
 ///
-pub fn get_task_kind(kind: &str) -> Result<String, ErrorKind> {
-    match kind.to_lowercase().as_str() {
-        "openwhisk" => Ok("OpenWhisk".to_string()),
-        "polkadot" => Ok("Polkadot".to_string()),
-        "hello_world" => Ok("HelloWorldDerive".to_string()),
-        _ => Err(ErrorKind::NotFound),
+pub fn get_task_kind(task_kinds: &TaskKinds, kind: &str) -> Result<String, ErrorKind> {
+    task_kinds
+        .get(kind)
+        .map(|kind| kind.derive().to_string())
+        .ok_or(ErrorKind::NotFound)
+}
+
+/// Keywords which cannot be escaped as raw identifiers
+const UNESCAPABLE_KEYWORDS: [&str; 5] = ["self", "Self", "super", "crate", "_"];
+
+/// Parses a name into the identifier it is generated as, escaping the Rust keywords as
+/// raw identifiers, such as `r#type`
+///
+/// # Arguments
+///
+/// * `name` - The name to generate, already converted to the case of the identifier
+///
+/// # Returns
+///
+/// * An Err Result if the name is not an identifier, such as `2fa` or `my-task`, or is a
+///   keyword which cannot be escaped, such as `self`
+///
+pub fn get_rust_ident(name: &str) -> Result<Ident, Error> {
+    if let Ok(ident) = syn::parse_str::<Ident>(name) {
+        return Ok(ident);
+    }
+
+    if UNESCAPABLE_KEYWORDS.contains(&name) {
+        return Err(Error::msg(format!(
+            "`{}` is a Rust keyword which cannot be used as a name",
+            name
+        )));
+    }
+
+    match syn::parse::Parser::parse_str(Ident::parse_any, name) {
+        Ok(_) => Ok(Ident::new_raw(name, Span::call_site())),
+        Err(_) => Err(Error::msg(format!(
+            "`{}` is not a valid Rust identifier",
+            name
+        ))),
+    }
+}
+
+/// Names the generated code declares for the workflow itself, which the types and
+/// variables of the tasks would collide with
+pub const RESERVED_NAMES: [&str; 5] = ["Input", "args", "input", "workflow", "result"];
+
+/// Validates that the name of a task can be generated as the names of its Rust type, in
+/// Pascal case, and of its variable, in snake case
+///
+/// # Arguments
+///
+/// * `action_name` - The name of the task
+///
+/// # Returns
+///
+/// * An Err Result if one of the names is not an identifier or is reserved by the code
+///   generated for the workflow
+///
+pub fn validate_task_name(action_name: &str) -> Result<(), Error> {
+    for name in [
+        action_name.to_case(Case::Pascal),
+        action_name.to_case(Case::Snake),
+    ] {
+        if name.is_empty() {
+            return Err(Error::msg(format!(
+                "The task name '{}' has no letters or digits to generate Rust code from",
+                action_name
+            )));
+        }
+
+        if RESERVED_NAMES.contains(&name.as_str()) {
+            return Err(Error::msg(format!(
+                "The task name '{}' is generated as `{}`, which is reserved by the generated code",
+                action_name, name
+            )));
+        }
+
+        get_rust_ident(&name).map_err(|err| {
+            Error::msg(format!(
+                "The task name '{}' cannot be generated: {}",
+                action_name, err
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Names of the Rust prelude of the 2018 edition, which the generated crates are built
+/// with and the types generated for a workflow would shadow
+const PRELUDE_NAMES: [&str; 35] = [
+    "AsMut",
+    "AsRef",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "DoubleEndedIterator",
+    "Drop",
+    "Eq",
+    "Err",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "Into",
+    "IntoIterator",
+    "Iterator",
+    "None",
+    "Ok",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Send",
+    "Sized",
+    "Some",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "Unpin",
+    "Vec",
+];
+
+/// Collects the names imported by a `use` declaration, skipping the glob imports, which
+/// the boilerplate only uses for its own modules and the kinds cannot use
+fn collect_use_names(tree: &syn::UseTree, names: &mut HashSet<String>) {
+    match tree {
+        syn::UseTree::Path(path) => collect_use_names(&path.tree, names),
+        syn::UseTree::Name(name) if name.ident != "self" => {
+            names.insert(name.ident.to_string());
+        }
+        syn::UseTree::Rename(rename) => {
+            names.insert(rename.rename.to_string());
+        }
+        syn::UseTree::Group(group) => {
+            for tree in group.items.iter() {
+                collect_use_names(tree, names);
+            }
+        }
+        _ => {}
     }
 }
 
-fn get_main_method_code_template(tasks_length: usize) -> String {
-    format!(
-        "#[allow(dead_code, unused)]
-pub fn main(args: Value) -> Result<Value, String> {{
-    const LIMIT: usize = {tasks_length};
-    let mut workflow = WorkflowGraph::new(LIMIT);
-    let input: Input = serde_json::from_value(args).map_err(|e| e.to_string())?;
-"
-    )
+/// Collects the names declared or imported by a sequence of items
+fn collect_item_names(items: &[syn::Item], names: &mut HashSet<String>) {
+    for item in items {
+        let ident = match item {
+            syn::Item::Use(item) => {
+                collect_use_names(&item.tree, names);
+                continue;
+            }
+            syn::Item::ForeignMod(item) => {
+                for item in item.items.iter() {
+                    match item {
+                        syn::ForeignItem::Fn(item) => names.insert(item.sig.ident.to_string()),
+                        syn::ForeignItem::Static(item) => names.insert(item.ident.to_string()),
+                        syn::ForeignItem::Type(item) => names.insert(item.ident.to_string()),
+                        _ => false,
+                    };
+                }
+                continue;
+            }
+            syn::Item::Const(item) => &item.ident,
+            syn::Item::Enum(item) => &item.ident,
+            syn::Item::ExternCrate(item) => &item.ident,
+            syn::Item::Fn(item) => &item.sig.ident,
+            syn::Item::Mod(item) => &item.ident,
+            syn::Item::Static(item) => &item.ident,
+            syn::Item::Struct(item) => &item.ident,
+            syn::Item::Trait(item) => &item.ident,
+            syn::Item::Type(item) => &item.ident,
+            syn::Item::Union(item) => &item.ident,
+            _ => continue,
+        };
+
+        names.insert(ident.to_string());
+    }
 }
 
-/// Formats the attributes from the given HashMap into a specific string format
-/// This string will be passed to the macros as arguments
+/// Gets the names in scope of the generated `types.rs`, which imports the items of the
+/// boilerplate, of the kinds used by the workflow and of the Rust prelude through
+/// `use super::*`
 ///
 /// # Arguments
 ///
-/// * `map` - A reference to the HashMap containing attribute key-value pairs
+/// * `workflow` - The workflow the code is generated for
+/// * `task_kinds` - The registry of the task kinds known to the composer
 ///
 /// # Returns
 ///
-/// * A String containing formatted attribute key-value pairs enclosed in square brackets
+/// * `HashSet<String>` - The names which the types generated for the tasks and the
+///   EchoStructs of the workflow must not take
 ///
-/// This formats the value of the attributes as enclosed by double quots
-pub fn get_attributes(attributes: &HashMap<String, String>) -> String {
-    let mut build_string = Vec::new();
+pub fn get_generated_scope(
+    workflow: &Workflow,
+    task_kinds: &TaskKinds,
+) -> Result<HashSet<String>, Error> {
+    let mut names: HashSet<String> = PRELUDE_NAMES.iter().map(|name| name.to_string()).collect();
+
+    let sources = [
+        (COMMON.to_string(), "the boilerplate"),
+        (LIB.to_string(), "the boilerplate"),
+        (TRAIT.to_string(), "the boilerplate"),
+        (get_kind_library_code(workflow, task_kinds), "the kinds"),
+    ];
+
+    for (code, origin) in sources {
+        let file: syn::File = syn::parse2(parse_items(&code, origin)?)?;
+        collect_item_names(&file.items, &mut names);
+    }
 
-    for (key, value) in attributes {
-        build_string.push(format!("{}:\"{}\"", key.to_case(Case::Pascal), value));
+    let imports: syn::File = syn::parse2(get_kind_imports(workflow, task_kinds)?)?;
+    collect_item_names(&imports.items, &mut names);
+
+    Ok(names)
+}
+
+/// Parses a type of the config files into the Rust type it is generated as, the names of
+/// the custom types being validated as identifiers
+pub fn get_rust_type(rust_type: &RustType) -> Result<syn::Type, Error> {
+    for name in rust_type.custom_types() {
+        get_rust_ident(name)?;
     }
 
-    format!("[{}]", build_string.join(","))
+    syn::parse_str::<syn::Type>(&rust_type.to_string())
+        .map_err(|err| Error::msg(format!("`{}` is not a valid Rust type: {}", rust_type, err)))
 }
 
+/// Parses the Rust code given as a string by a kind or a custom type, so that code which
+/// is not a sequence of items is reported before being generated
+///
+/// # Arguments
+///
+/// * `code` - The Rust code to parse
+/// * `origin` - The origin of the code, naming it in the errors
+///
+fn parse_items(code: &str, origin: &str) -> Result<TokenStream, Error> {
+    let file = syn::parse_file(code)
+        .map_err(|err| Error::msg(format!("Invalid Rust code for {}: {}", origin, err)))?;
+
+    Ok(quote!(#file))
+}
+
+fn get_main_method_code(tasks_length: usize, body: TokenStream) -> TokenStream {
+    let tasks_length = Literal::usize_unsuffixed(tasks_length);
+
+    quote! {
+        #[allow(dead_code, unused)]
+        pub fn main(args: Value) -> Result<Value, String> {
+            const LIMIT: usize = #tasks_length;
+            let mut workflow = WorkflowGraph::new(LIMIT);
+            let input: Input = serde_json::from_value(args)
+                .map_err(|e| record_error(None, WorkflowErrorKind::Input, e.to_string()))?;
+            #body
+        }
+    }
+}
+
+/// Generates the attributes from the given IndexMap, passed to the macros as arguments
+///
+/// # Arguments
+///
+/// * `map` - A reference to the IndexMap containing attribute key-value pairs
+///
+/// # Returns
+///
+/// * A TokenStream containing the attribute key-value pairs enclosed in square brackets
+///
+/// The values of the attributes are generated as string literals
+pub fn get_attributes(attributes: &IndexMap<String, String>) -> Result<TokenStream, Error> {
+    let mut build_attributes = Vec::new();
+
+    for (key, value) in attributes {
+        let key = get_rust_ident(&key.to_case(Case::Pascal))?;
+        let value = LitStr::new(value, Span::call_site());
+
+        build_attributes.push(quote!(#key: #value));
+    }
+
+    Ok(quote!([#(#build_attributes),*]))
+}
 
-fn get_default_value_functions_code(workflow: &Workflow) -> String {
-    let mut default_value_functions = String::new();
+fn get_default_value_functions_code(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let mut default_value_functions = TokenStream::new();
+    let mut generated = HashSet::new();
 
     for task in workflow.tasks.values() {
         for input in task.input_arguments.iter() {
-            if !input.is_depend {
+            // Tasks sharing an argument share the function of its default value
+            if !input.is_depend && generated.insert(&input.name) {
                 if let Some(val) = input.default_value.as_ref() {
+                    let name = get_rust_ident(&format!("{}_fn", input.name))?;
+                    let input_type = get_rust_type(&input.input_type)?;
+                    let val = LitStr::new(val, Span::call_site());
+
                     let content = match input.input_type {
-                        RustType::String => format!("{val:?}.to_string()"),
-                        _ => format!(
-                            "let val = serde_json::from_str::<{}>({:?}).unwrap();val",
-                            input.input_type, val
-                        ),
+                        RustType::String => quote!(#val.to_string()),
+                        _ => quote! {
+                            let val = serde_json::from_str::<#input_type>(#val).unwrap();
+                            val
+                        },
                     };
 
-                    let make_fn = format!(
-                        "pub fn {}_fn() -> {}{{{}}}\n",
-                        input.name, input.input_type, content
-                    );
-
-                    default_value_functions.push_str(&make_fn);
+                    default_value_functions.extend(quote! {
+                        pub fn #name() -> #input_type {
+                            #content
+                        }
+                    });
                 }
             };
         }
     }
 
-    default_value_functions
+    Ok(default_value_functions)
 }
 
 /// Creates a Rust code to generate a struct with fields representing inputs not
@@ -92,117 +348,207 @@ fn get_default_value_functions_code(workflow: &Workflow) -> String {
 ///
 /// # Returns
 ///
-/// * A String containing Rust code to create a struct representing inputs not depending
-///   on any task
+/// * A TokenStream containing Rust code to create a struct representing inputs not
+///   depending on any task
 ///
 fn get_task_common_input_type_constructor(
-    composer_custom_types: &HashMap<String, String>,
+    composer_custom_types: &IndexMap<String, String>,
     workflow: &Workflow,
-) -> Result<String, Error> {
-    let mut common = Vec::<String>::new();
-    let mut workflow_custom_types = Vec::<String>::new();
+) -> Result<TokenStream, Error> {
+    let mut common = Vec::<TokenStream>::new();
+    let mut common_names = HashSet::new();
+    let mut workflow_custom_types = IndexSet::<String>::new();
 
     for task in workflow.tasks.values() {
+        for output_type in task.output.values() {
+            if let RustType::Struct(name) = output_type {
+                workflow_custom_types.insert(name.to_string());
+            }
+        }
+
         for input in task.input_arguments.iter() {
             if let RustType::Struct(name) = &input.input_type {
-                workflow_custom_types.push(name.to_string());
+                workflow_custom_types.insert(name.to_string());
             }
 
-            if !input.is_depend {
+            // Tasks sharing an argument read it from the same field of the input
+            if !input.is_depend && common_names.insert(&input.name) {
+                let name = get_rust_ident(&input.name)?;
+                let input_type = get_rust_type(&input.input_type)?;
+
                 if input.default_value.as_ref().is_some() {
-                    common.push(format!(
-                        "#[\"{}_fn\"] {}:{}",
-                        input.name, input.name, input.input_type
-                    ));
+                    let default = LitStr::new(&format!("{}_fn", input.name), Span::call_site());
+                    common.push(quote!(#[#default] #name: #input_type));
                 } else {
-                    common.push(format!("{}:{}", input.name, input.input_type));
+                    common.push(quote!(#name: #input_type));
                 };
             }
         }
     }
 
-    let workflow_custom_types = if !workflow_custom_types.is_empty() {
-        let mut build_string = String::new();
+    let mut build_custom_types = TokenStream::new();
 
-        for custom_type in workflow_custom_types.iter() {
-            let typ = match composer_custom_types.get(custom_type) {
-                Some(t) => t,
-                None => return Err(Error::msg("Missing custom type in workflow")),
-            };
-            build_string = format!("{build_string}{typ}");
-        }
+    for custom_type in workflow_custom_types.iter() {
+        let typ = match composer_custom_types.get(custom_type) {
+            Some(t) => t,
+            None => return Err(Error::msg("Missing custom type in workflow")),
+        };
 
-        build_string
-    } else {
-        "".to_string()
-    };
-    Ok(format!(
-        "{workflow_custom_types}
-make_input_struct!(
-Input,
-[{}],
-[Debug, Clone, Default, Serialize, Deserialize]
-);",
-        common.join(",")
-    ))
+        build_custom_types.extend(parse_items(typ, &format!("the type {}", custom_type))?);
+    }
+
+    Ok(quote! {
+        #build_custom_types
+        make_input_struct!(
+            Input,
+            [#(#common),*],
+            [Debug, Clone, Default, Serialize, Deserialize]
+        );
+    })
 }
 
-fn get_task_type_constructors(workflow: &Workflow) -> String {
-    let mut constructors = String::new();
+fn get_task_type_constructors(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let mut constructors = TokenStream::new();
 
     for task in workflow.tasks.values() {
-        let mut parameters = String::new();
+        let mut parameters = Vec::new();
 
         for argument in task.input_arguments.iter() {
             if !argument.is_depend {
-                parameters.push_str(&format!("input.{},", argument.name));
+                let name = get_rust_ident(&argument.name)?;
+                parameters.push(quote!(input.#name,));
             }
         }
 
-        let constructor = format!(
-            "let {} = {}::new({}\"{}\".to_string());\n",
-            task.action_name.to_case(Case::Snake),
-            task.action_name.to_case(Case::Pascal),
-            parameters,
-            task.action_name.clone()
-        );
+        let variable = get_rust_ident(&task.action_name.to_case(Case::Snake))?;
+        let task_type = get_rust_ident(&task.action_name.to_case(Case::Pascal))?;
+        let action_name = LitStr::new(&task.action_name, Span::call_site());
 
-        constructors.push_str(&constructor);
+        constructors.extend(quote! {
+            let #variable = #task_type::new(#(#parameters)* #action_name.to_string());
+        });
     }
 
-    constructors
+    Ok(constructors)
 }
 
-fn get_task_input_type_constructors(workflow: &Workflow) -> String {
-    let mut input_type_build_string = String::new();
+fn get_task_input_type_constructors(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let mut input_type_build = TokenStream::new();
 
     for task in workflow.tasks.values() {
         let mut arguments = Vec::new();
 
         for field in task.input_arguments.iter() {
-            arguments.push(format!("{}:{}", field.name, field.input_type));
+            let name = get_rust_ident(&field.name)?;
+            let input_type = get_rust_type(&field.input_type)?;
+
+            arguments.push(quote!(#name: #input_type));
         }
 
-        input_type_build_string.push_str(&format!(
-            "make_input_struct!(\n{}Input,\n[{}],\n[Debug, Clone, Default, Serialize, Deserialize]\n);",
-            task.action_name.to_case(Case::Pascal),
-            arguments.join(",")
-        ));
+        let input = get_rust_ident(&format!("{}Input", task.action_name.to_case(Case::Pascal)))?;
+
+        input_type_build.extend(quote! {
+            make_input_struct!(
+                #input,
+                [#(#arguments),*],
+                [Debug, Clone, Default, Serialize, Deserialize]
+            );
+        });
     }
 
-    input_type_build_string
+    Ok(input_type_build)
 }
 
-fn get_independent_fields(task: &Task) -> Vec<String> {
-    let mut independent_fields = Vec::<String>::new();
+/// Generates Rust code to create a struct for the output of each task declaring its
+/// output fields, so the setters of the dependent tasks read them with their types
+///
+/// # Arguments
+///
+/// * `workflow` - The workflow whose tasks are generated
+///
+/// # Returns
+///
+/// * A TokenStream containing Rust code to create the `<Task>Output` structs
+///
+fn get_task_output_type_constructors(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let mut output_type_build = TokenStream::new();
+
+    for task in workflow.tasks.values() {
+        if task.output.is_empty() {
+            continue;
+        }
+
+        let mut fields = Vec::new();
+
+        for (name, output_type) in task.output.iter() {
+            let name = get_rust_ident(name)?;
+            let output_type = get_rust_type(output_type)?;
+
+            fields.push(quote!(pub #name: #output_type));
+        }
+
+        let output = get_rust_ident(&format!("{}Output", task.action_name.to_case(Case::Pascal)))?;
+
+        output_type_build.extend(quote! {
+            make_input_struct!(
+                #output,
+                [#(#fields),*],
+                [Debug, Clone, Default, Serialize, Deserialize]
+            );
+        });
+    }
+
+    Ok(output_type_build)
+}
+
+/// Generates the key a setter reads a dependency with, naming the `<Task>Output` struct
+/// of the upstream task when it declares its output so the field is read with its type
+///
+/// # Arguments
+///
+/// * `workflow` - The workflow containing the upstream task
+/// * `dependent` - The dependency of the task on the field of the upstream task
+///
+/// # Returns
+///
+/// * A TokenStream containing either `field as <Task>Output` or the quoted field name,
+///   followed by the name of the upstream task the errors of the setter refer to
+///
+fn get_setter_key(workflow: &Workflow, dependent: &Depend) -> Result<TokenStream, Error> {
+    let key = match workflow.tasks.get(&dependent.task_name) {
+        Some(task)
+            if !task.operation.is_map() && task.output.contains_key(&dependent.prev_field) =>
+        {
+            let field = get_rust_ident(&dependent.prev_field)?;
+            let output =
+                get_rust_ident(&format!("{}Output", task.action_name.to_case(Case::Pascal)))?;
+
+            quote!(#field as #output)
+        }
+        _ => {
+            let field = LitStr::new(&dependent.prev_field, Span::call_site());
+            quote!(#field)
+        }
+    };
+
+    let upstream = LitStr::new(&dependent.task_name, Span::call_site());
+
+    Ok(quote!(#key => #upstream))
+}
+
+fn get_independent_fields(task: &Task) -> Result<Vec<TokenStream>, Error> {
+    let mut independent_fields = Vec::<TokenStream>::new();
 
     for field in task.input_arguments.iter() {
         if !field.is_depend {
-            independent_fields.push(format!("{}:{}", field.name, field.input_type));
+            let name = get_rust_ident(&field.name)?;
+            let input_type = get_rust_type(&field.input_type)?;
+
+            independent_fields.push(quote!(#name: #input_type));
         }
     }
 
-    independent_fields
+    Ok(independent_fields)
 }
 
 /// Generates Rust code to create structs for each task and its input, and creates object
@@ -214,55 +560,73 @@ fn get_independent_fields(task: &Task) -> Vec<String> {
 ///
 /// # Returns
 ///
-/// * An array of Strings containing Rust code to create structs and objects for the
-///   specified workflow
+/// * A TokenStream containing Rust code to create structs and objects for the specified
+///   workflow
 ///
-fn get_task_main_type_constructors(workflow: &Workflow) -> Result<String, Error> {
-    let mut input_structs = String::new();
+fn get_task_main_type_constructors(
+    workflow: &Workflow,
+    task_kinds: &TaskKinds,
+) -> Result<TokenStream, Error> {
+    let mut input_structs = TokenStream::new();
 
     for (task_name, task) in workflow.tasks.iter() {
         let task_name = task_name.to_case(Case::Pascal);
+        let task_type = get_rust_ident(&task_name)?;
+        let input_type = get_rust_ident(&format!("{}Input", task_name))?;
 
-        let independent_fields = get_independent_fields(task);
+        let independent_fields = get_independent_fields(task)?;
 
         let output_field = if task.operation.is_map() {
-            "mapout"
+            quote!(mapout)
         } else {
-            "output"
+            quote!(output)
         };
 
-        input_structs = format!(
-            "{input_structs}
-make_main_struct!(
-    {task_name},
-    {task_name}Input,
-    [Debug, Clone, Default, Serialize, Deserialize, {}],
-    {},
-    {}
-);
-impl_new!(
-    {task_name},
-    {task_name}Input,
-    [{}]
-);
-",
-            get_task_kind(&task.kind).unwrap(),
-            get_attributes(&task.attributes),
-            output_field,
-            independent_fields.join(",")
-        );
+        let kind = task_kinds
+            .get(&task.kind)
+            .ok_or_else(|| Error::msg(format!("Unsupported task kind: {}", task.kind)))?;
+
+        // Kinds generating the execution of their tasks themselves declare no derive macro
+        let derive = if kind.derive().is_empty() {
+            None
+        } else {
+            let derive = get_rust_ident(kind.derive())?;
+            Some(quote!(, #derive))
+        };
+
+        let attributes = get_attributes(&task.attributes)?;
+        let task_code = parse_items(
+            &kind.task_code(&task_name),
+            &format!("the kind {}", task.kind),
+        )?;
+
+        input_structs.extend(quote! {
+            make_main_struct!(
+                #task_type,
+                #input_type,
+                [Debug, Clone, Default, Serialize, Deserialize #derive],
+                #attributes,
+                #output_field
+            );
+            impl_new!(
+                #task_type,
+                #input_type,
+                [#(#independent_fields),*]
+            );
+            #task_code
+        });
     }
 
     Ok(input_structs)
 }
 
-fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
-    let mut impl_setters_code = String::new();
+fn get_impl_setters_code(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let mut impl_setters_code = TokenStream::new();
 
     for (task_name, task) in workflow.tasks.iter() {
-        let task_name = task_name.to_case(Case::Pascal);
+        let task_type = get_rust_ident(&task_name.to_case(Case::Pascal))?;
 
-        let mut setter_fields = Vec::<String>::new();
+        let mut setter_fields = Vec::<TokenStream>::new();
 
         let mut set = HashMap::<String, i32>::new();
         let mut index: i32 = 0;
@@ -276,6 +640,9 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
                 &index
             };
 
+            let current_index = Literal::i32_unsuffixed(*current_index);
+            let cur_field = get_rust_ident(&dependent.cur_field)?;
+
             if task.operation.is_combine() {
                 let dependent_task = match workflow.tasks.get(&dependent.task_name) {
                     Some(t) => t,
@@ -283,51 +650,55 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
                 };
 
                 if dependent_task.operation.is_map() {
-                    setter_fields.push(format!(
-                        "(value)[{}]{}:\"{}\"",
-                        current_index, dependent.cur_field, dependent.prev_field
-                    ));
+                    let prev_field = LitStr::new(&dependent.prev_field, Span::call_site());
+                    let upstream = LitStr::new(&dependent.task_name, Span::call_site());
+
+                    setter_fields.push(quote! {
+                        (value)[#current_index] #cur_field: #prev_field => #upstream
+                    });
                 } else {
-                    setter_fields.push(format!(
-                        "[{}]{}:\"{}\"",
-                        current_index, dependent.cur_field, dependent.prev_field
-                    ));
+                    let key = get_setter_key(workflow, dependent)?;
+
+                    setter_fields.push(quote!([#current_index] #cur_field: #key));
                 }
             } else {
-                setter_fields.push(format!(
-                    "{}:\"{}\"",
-                    dependent.cur_field, dependent.prev_field
-                ));
+                let key = get_setter_key(workflow, dependent)?;
+
+                setter_fields.push(quote!(#cur_field: #key));
             }
 
             index += 1;
         }
 
-        let setter_build_string = match &task.operation {
-            Operation::Map(field) => format!(
-                "impl_map_setter!({}, {}, {}, \"{}\");\n",
-                task_name,
-                setter_fields.join(","),
-                task.input_arguments[0].input_type,
-                field
-            ),
-            Operation::Concat => format!(
-                "impl_concat_setter!({}, {});\n",
-                task_name, task.input_arguments[0].name
-            ),
-            Operation::Combine => format!(
-                "impl_combine_setter!({},[{}]);\n",
-                task_name,
-                setter_fields.join(","),
-            ),
-            _ => format!(
-                "impl_setter!({}, [{}]);\n",
-                task_name,
-                setter_fields.join(",")
-            ),
+        let setter_build = match &task.operation {
+            Operation::Map(field) => {
+                let element = task.map_element().ok_or_else(|| {
+                    Error::msg(format!(
+                        "Map task '{}' must depend on the list of its elements",
+                        task.action_name
+                    ))
+                })?;
+                let input_type = get_rust_type(&element.input_type)?;
+                let field = LitStr::new(field, Span::call_site());
+
+                quote!(impl_map_setter!(#task_type, #(#setter_fields),*, #input_type, #field);)
+            }
+            Operation::Concat => {
+                let input = get_rust_ident(&task.input_arguments[0].name)?;
+                let upstreams = task
+                    .depend_on
+                    .iter()
+                    .map(|dependent| LitStr::new(&dependent.task_name, Span::call_site()));
+
+                quote!(impl_concat_setter!(#task_type, #input, [#(#upstreams),*]);)
+            }
+            Operation::Combine => {
+                quote!(impl_combine_setter!(#task_type, [#(#setter_fields),*]);)
+            }
+            _ => quote!(impl_setter!(#task_type, [#(#setter_fields),*]);),
         };
 
-        impl_setters_code.push_str(&setter_build_string);
+        impl_setters_code.extend(setter_build);
     }
 
     Ok(impl_setters_code)
@@ -342,46 +713,56 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
 ///
 /// # Returns
 ///
-/// * A String containing the Rust code to call the `impl_execute_trait!` macro
+/// * A TokenStream containing the Rust code to call the `impl_execute_trait!` macro
 ///
-fn get_impl_execute_trait_code(workflow: &Workflow) -> String {
+fn get_impl_execute_trait_code(workflow: &Workflow) -> Result<TokenStream, Error> {
     let mut task_names = Vec::new();
 
     for task_name in workflow.tasks.keys() {
-        task_names.push(task_name.to_case(Case::Pascal));
+        task_names.push(get_rust_ident(&task_name.to_case(Case::Pascal))?);
     }
 
-    format!("impl_execute_trait!({});", task_names.join(","))
+    Ok(quote!(impl_execute_trait!(#(#task_names),*);))
 }
 
-fn get_add_nodes_code(flow: &Vec<String>) -> String {
-    let mut add_nodes_code = String::new();
+/// Generates the identifier of the variable holding the task, and of its index in the
+/// graph of the workflow
+fn get_task_variables(task_name: &str) -> Result<(Ident, Ident), Error> {
+    let variable = task_name.to_case(Case::Snake);
+
+    Ok((
+        get_rust_ident(&variable)?,
+        get_rust_ident(&format!("{}_index", variable))?,
+    ))
+}
+
+fn get_add_nodes_code(flow: &[String]) -> Result<TokenStream, Error> {
+    let mut add_nodes_code = TokenStream::new();
 
     for i in flow {
-        add_nodes_code.push_str(&format!(
-            "let {}_index = workflow.add_node(Box::new({}));\n",
-            i.to_case(Case::Snake),
-            i.to_case(Case::Snake)
-        ));
+        let (variable, index) = get_task_variables(i)?;
+
+        add_nodes_code.extend(quote! {
+            let #index = workflow.add_node(Box::new(#variable));
+        });
     }
 
-    add_nodes_code
+    Ok(add_nodes_code)
 }
 
-fn get_add_edges_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String, Error> {
-    let mut add_edges_code = "workflow.add_edges(&[\n".to_string();
+fn get_add_edges_code(workflow: &Workflow, flow: &[String]) -> Result<TokenStream, Error> {
+    let mut edges = Vec::new();
 
     for index in 0..flow.len() - 1 {
         if let Some(dependent_task) = workflow.tasks.get(&flow[index + 1]) {
             let mut set = HashSet::<String>::new();
+            let (_, next_index) = get_task_variables(&flow[index + 1])?;
 
             for dependent_task in dependent_task.depend_on.iter() {
                 if !set.contains(&dependent_task.task_name) {
-                    add_edges_code = format!(
-                        "{add_edges_code}({}_index, {}_index),\n",
-                        dependent_task.task_name.to_case(Case::Snake),
-                        flow[index + 1].to_case(Case::Snake)
-                    );
+                    let (_, dependent_index) = get_task_variables(&dependent_task.task_name)?;
+
+                    edges.push(quote!((#dependent_index, #next_index)));
                     set.insert(dependent_task.task_name.clone());
                 }
             }
@@ -390,14 +771,18 @@ fn get_add_edges_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String,
         }
     }
 
-    add_edges_code += "]);";
-    Ok(add_edges_code)
+    Ok(quote!(workflow.add_edges(&[#(#edges),*]);))
 }
 
-fn get_add_execute_workflow_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String, Error> {
-    let mut execute_code = "let result = workflow\n.init()?".to_string();
+fn get_add_execute_workflow_code(
+    workflow: &Workflow,
+    flow: &[String],
+) -> Result<TokenStream, Error> {
+    let mut execute_code = quote!(workflow.init()?);
 
     for task_index in 0..flow.len() - 1 {
+        let (_, index) = get_task_variables(&flow[task_index + 1])?;
+
         execute_code = if task_index + 1 == flow.len() - 1 {
             match workflow
                 .tasks
@@ -406,29 +791,15 @@ fn get_add_execute_workflow_code(workflow: &Workflow, flow: &Vec<String>) -> Res
                 .depend_on
                 .len()
             {
-                0 | 1 => {
-                    format!(
-                        "{execute_code}\n.term(Some({}_index))?;",
-                        flow[task_index + 1].to_case(Case::Snake)
-                    )
-                }
-
-                _ => {
-                    format!(
-                        "{execute_code}\n.pipe({}_index)?\n.term(None)?;",
-                        flow[task_index + 1].to_case(Case::Snake)
-                    )
-                }
+                0 | 1 => quote!(#execute_code.term(Some(#index))?),
+                _ => quote!(#execute_code.pipe(#index)?.term(None)?),
             }
         } else {
-            format!(
-                "{execute_code}\n.pipe({}_index)?",
-                flow[task_index + 1].to_case(Case::Snake)
-            )
+            quote!(#execute_code.pipe(#index)?)
         }
     }
 
-    Ok(execute_code)
+    Ok(quote!(let result = #execute_code;))
 }
 
 /// Generates Rust code to add workflow nodes and edges
@@ -439,41 +810,48 @@ fn get_add_execute_workflow_code(workflow: &Workflow, flow: &Vec<String>) -> Res
 ///
 /// # Returns
 ///
-/// * An array containing the Rust code to add workflow nodes and edges
+/// * A TokenStream containing the Rust code to add workflow nodes and edges
 ///
-fn get_workflow_nodes_and_edges_code(workflow: &Workflow) -> Result<String, Error> {
-    let flow: Vec<String> = workflow.get_flow();
+fn get_workflow_nodes_and_edges_code(workflow: &Workflow) -> Result<TokenStream, Error> {
+    let flow: Vec<String> = workflow.get_flow()?;
 
     if flow.is_empty() {
-        return Ok("".to_string());
+        return Ok(TokenStream::new());
     }
 
     if flow.len() == 1 {
-        return Ok(format!(
-            "\
-let {}_index = workflow.add_node(Box::new({}));
-\tlet result = workflow\n\t\t.init()?
-\t\t.term(None)?;
-Ok(result)
-",
-            flow[0].to_case(Case::Snake),
-            flow[0].to_case(Case::Snake)
-        ));
+        let (variable, index) = get_task_variables(&flow[0])?;
+
+        return Ok(quote! {
+            let #index = workflow.add_node(Box::new(#variable));
+            let result = workflow.init()?.term(None)?;
+            Ok(result)
+        });
     }
 
-    Ok(format!(
-        "{}\n{}\n{}let result = serde_json::to_value(result).unwrap();\nOk(result)",
-        get_add_nodes_code(&flow),
-        get_add_edges_code(workflow, &flow)?,
-        get_add_execute_workflow_code(workflow, &flow)?,
-    ))
+    let add_nodes_code = get_add_nodes_code(&flow)?;
+    let add_edges_code = get_add_edges_code(workflow, &flow)?;
+    let execute_code = get_add_execute_workflow_code(workflow, &flow)?;
+
+    Ok(quote! {
+        #add_nodes_code
+        #add_edges_code
+        #execute_code
+        let result = serde_json::to_value(result).unwrap();
+        Ok(result)
+    })
 }
 
 /// Generates the main Rust code for the workflow package and creates the `types.rs` file
 ///
+/// The code is generated as a typed syntax tree, so that the names of the config files
+/// are validated as identifiers and their values escaped as literals, and pretty-printed.
+///
 /// # Arguments
 ///
-/// * `workflow_index` - The index of the workflow
+/// * `workflow` - The workflow to generate the code for
+/// * `custom_types` - The user-defined types created within the config files
+/// * `task_kinds` - The registry of the task kinds known to the composer
 ///
 /// # Returns
 ///
@@ -481,191 +859,315 @@ Ok(result)
 ///
 pub fn generate_types_rs_file_code(
     workflow: &Workflow,
-    custom_types: &HashMap<String, String>,
+    custom_types: &IndexMap<String, String>,
+    task_kinds: &TaskKinds,
 ) -> Result<String, Error> {
-    let main_file = format!(
-        "use super::*;\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}}}",
-        add_polkadot_openwhisk(workflow),
-        get_task_input_type_constructors(workflow),
-        get_task_main_type_constructors(workflow)?,
-        get_impl_setters_code(workflow)?,
-        get_default_value_functions_code(workflow),
-        get_task_common_input_type_constructor(custom_types, workflow)?,
-        get_impl_execute_trait_code(workflow),
-        get_main_method_code_template(workflow.tasks.len()),
-        get_task_type_constructors(workflow),
-        get_workflow_nodes_and_edges_code(workflow)?
+    let kind_imports = get_kind_imports(workflow, task_kinds)?;
+    let task_input_types = get_task_input_type_constructors(workflow)?;
+    let task_output_types = get_task_output_type_constructors(workflow)?;
+    let task_main_types = get_task_main_type_constructors(workflow, task_kinds)?;
+    let impl_setters = get_impl_setters_code(workflow)?;
+    let default_value_functions = get_default_value_functions_code(workflow)?;
+    let common_input_type = get_task_common_input_type_constructor(custom_types, workflow)?;
+    let impl_execute_trait = get_impl_execute_trait_code(workflow)?;
+
+    let task_types = get_task_type_constructors(workflow)?;
+    let nodes_and_edges = get_workflow_nodes_and_edges_code(workflow)?;
+    let main_method = get_main_method_code(
+        workflow.tasks.len(),
+        quote! {
+            #task_types
+            #nodes_and_edges
+        },
     );
-    Ok(main_file)
-}
 
-fn get_openwhisk_kind_dependencies() -> String {
-    "
-openwhisk_macro = \"0.1.6\"
+    let main_file = quote! {
+        use super::*;
+        #kind_imports
+        #task_input_types
+        #task_output_types
+        #task_main_types
+        #impl_setters
+        #default_value_functions
+        #common_input_type
+        #impl_execute_trait
+        #main_method
+    };
 
-"
-    .to_string()
-}
+    let main_file = syn::parse2::<syn::File>(main_file)
+        .map_err(|err| Error::msg(format!("Failed to generate types.rs file: {}", err)))?;
 
-fn get_polkadot_kind_dependencies() -> String {
-    // some of the polkadot dependencies
-    "substrate_macro = \"0.1.3\"
-    pallet-staking = { git = \"https://github.com/paritytech/substrate.git\", package = \"pallet-staking\", rev = \"eb1a2a8\" }
-    substrate-api-client = { git = \"https://github.com/HugoByte/substrate-api-client.git\", default-features = false, features = [\"staking-xt\"], branch =\"wasm-support\"}
-sp-core = { version = \"6.0.0\", default-features = false, features = [\"full_crypto\"], git = \"https://github.com/paritytech/substrate.git\", rev = \"eb1a2a8\" }
-sp-runtime = { version = \"6.0.0\", default-features = false, git = \"https://github.com/paritytech/substrate.git\", rev = \"eb1a2a8\" }
-     "
-        .to_string()
+    Ok(prettyplease::unparse(&main_file))
 }
 
-pub fn generate_cargo_toml_dependencies(workflow: &Workflow) -> String {
-    let mut dependency_map = HashMap::new();
-
-    let hello_world_dependency = "hello_world_macro = \"0.0.1\""
-    .to_string();
-
-    dependency_map.insert("hello_world", hello_world_dependency);
-    dependency_map.insert("openwhisk", get_openwhisk_kind_dependencies());
-    dependency_map.insert("polkadot", get_polkadot_kind_dependencies());
+/// Generates the dependencies to be appended to the `Cargo.toml` of the workflow package
+/// for the kinds of the tasks used in the workflow
+pub fn generate_cargo_toml_dependencies(workflow: &Workflow, task_kinds: &TaskKinds) -> String {
+    let mut dependencies = IndexSet::new();
 
-    let kinds = get_common_kind(workflow);
-    if kinds.is_empty() {
-        return String::new();
-    }
-
-    let mut toml_dependencies = String::new();
-    for (kind, dependency_string) in dependency_map.iter() {
-        if kinds.contains(&kind.to_string()) {
-            toml_dependencies.push_str(dependency_string);
+    for kind in get_common_kind(workflow) {
+        if let Some(kind) = task_kinds.get(&kind) {
+            dependencies.extend(kind.dependencies());
         }
     }
 
-    toml_dependencies
-}
-
-pub fn handle_multiple_dependency() -> String {
-    let openwhisk_dependency = get_openwhisk_kind_dependencies();
-    let polkadot_dependency = get_polkadot_kind_dependencies();
-
-    let combined_dependencies = format!("{}{}", openwhisk_dependency, polkadot_dependency);
-    combined_dependencies
+    dependencies
+        .into_iter()
+        .map(|dependency| format!("{dependency}\n"))
+        .collect()
 }
 
-pub fn get_polkadot() -> String {
-    "\
-    use substrate_macro::Polkadot;
-    use sp_core::H256;
-
-    "
-    .to_string()
-}
+/// Generates the name of the crate generated for the workflow, which names its artifacts
+///
+/// The crates are named after their workflow, so that the crates built in the same target
+/// directory do not overwrite the artifacts of each other.
+pub fn get_crate_name(workflow: &Workflow) -> String {
+    let name: String = format!("{}_{}", workflow.name, workflow.version)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
 
-pub fn get_openwhisk() -> String {
-    "\
-    use openwhisk_macro::*;
-    use openwhisk_rust::*;
-    
-    "
-    .to_string()
+    format!("workflow_{}", name)
 }
 
-pub fn add_polkadot_openwhisk(workflow: &Workflow) -> String {
-    let kinds = get_common_kind(workflow);
+/// Generates the `wit/world.wit` file of the component built for the workflow
+///
+/// The world exports a `run` function taking and returning JSON documents, like the
+/// `_start` ABI of the wasm module, with a typed error. Its documentation lists the
+/// arguments of the workflow and the output of its last task, as declared in the config.
+///
+/// # Arguments
+///
+/// * `workflow` - The workflow to generate the world for
+///
+/// # Returns
+///
+/// * A String containing the WIT package describing the component of the workflow
+///
+pub fn generate_wit_file_code(workflow: &Workflow) -> Result<String, Error> {
+    let name = workflow.name.to_case(Case::Kebab);
+
+    // Package versions must be semantic versions, which workflow versions may not be
+    let is_semver = workflow.version.split('.').count() == 3
+        && workflow
+            .version
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+
+    let package = if is_semver {
+        format!("composer:{}@{}", name, workflow.version)
+    } else {
+        format!("composer:{}", name)
+    };
 
-    let mut toml_dependencies = String::new();
+    let mut arguments = IndexMap::new();
 
-    if kinds.contains("openwhisk") {
-        toml_dependencies = get_openwhisk();
+    for task in workflow.tasks.values() {
+        for input in task.input_arguments.iter().filter(|input| !input.is_depend) {
+            let optional = if input.default_value.is_some() {
+                ", optional"
+            } else {
+                ""
+            };
+            arguments.insert(
+                input.name.clone(),
+                format!(
+                    "    /// * `{}`: `{}`{}\n",
+                    input.name, input.input_type, optional
+                ),
+            );
+        }
     }
 
-    if kinds.contains("polkadot") {
-        toml_dependencies = get_polkadot();
-    }
+    let output = match workflow
+        .get_flow()?
+        .last()
+        .and_then(|task| workflow.tasks.get(task))
+    {
+        Some(task) if !task.output.is_empty() => task
+            .output
+            .iter()
+            .map(|(field, typ)| format!("    /// * `{}`: `{}`\n", field, typ))
+            .collect::<String>(),
+        _ => "    /// * `Value`\n".to_string(),
+    };
 
-    if kinds.contains("openwhisk") && kinds.contains("polkadot") {
-        toml_dependencies = handle_multiple_kinds();
-    }
+    Ok(format!(
+        "\
+package {package};
+
+/// Workflow `{}`, exchanging JSON documents
+world {name} {{
+    /// Step of the workflow that failed
+    enum error-kind {{
+        input,
+        dependency,
+        execution,
+        workflow,
+    }}
+
+    /// Failure of the workflow, naming the task that failed if any
+    record workflow-error {{
+        task: option<string>,
+        kind: error-kind,
+        message: string,
+    }}
+
+    /// Runs the workflow on the JSON object holding its arguments, returning its result
+    ///
+    /// Arguments:
+    ///
+{}    ///
+    /// Result:
+    ///
+{output}    export run: func(input: string) -> result<string, workflow-error>;
+}}
+",
+        workflow.name,
+        arguments.values().cloned().collect::<String>(),
+    ))
+}
 
-    if kinds.contains("hello_world") {
-        toml_dependencies += "\nuse hello_world_macro::HelloWorldDerive;";
+/// Checks whether a `use` declaration imports the items of a path with a glob
+fn has_glob_import(tree: &syn::UseTree) -> bool {
+    match tree {
+        syn::UseTree::Path(path) => has_glob_import(&path.tree),
+        syn::UseTree::Group(group) => group.items.iter().any(has_glob_import),
+        syn::UseTree::Glob(_) => true,
+        _ => false,
     }
-
-    toml_dependencies
 }
 
-pub fn staking_ledger() -> String {
-    "\
-use sp_runtime::AccountId32;
-
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
- pub struct StakingLedger {
- pub stash: AccountId32,
- #[codec(compact)]
- pub total: u128,
- #[codec(compact)]
- pub active: u128,
- pub unlocking: Vec<u32>,
- pub claimed_rewards: Vec<u32>,
-}
-    "
-    .to_string()
-}
+/// Generates the `use` statements required by the kinds of the tasks used in the workflow
+///
+/// The kinds import their items by name, so that the names in scope of the generated code
+/// are read from the imports and checked against the names generated for the workflow
+pub fn get_kind_imports(workflow: &Workflow, task_kinds: &TaskKinds) -> Result<TokenStream, Error> {
+    let mut imports = IndexSet::new();
+
+    for kind in get_common_kind(workflow) {
+        if let Some(task_kind) = task_kinds.get(&kind) {
+            for import in task_kind.imports() {
+                imports.insert((kind.clone(), import));
+            }
+        }
+    }
 
-pub fn get_struct_stake_ledger(workflow: &Workflow) -> String {
-    let kinds = get_common_kind(workflow);
+    let mut build_imports = TokenStream::new();
 
-    let mut toml_dependencies = String::new();
+    for (kind, import) in imports {
+        let origin = format!("the kind {}", kind);
+        let items = parse_items(&import, &origin)?;
 
-    if kinds.contains("polkadot") {
-        toml_dependencies = staking_ledger();
+        let file: syn::File = syn::parse2(items.clone())?;
+        for item in file.items.iter() {
+            if let syn::Item::Use(item) = item {
+                if has_glob_import(&item.tree) {
+                    return Err(Error::msg(format!(
+                        "Invalid import for {}: `{}` is a glob import, import the items it uses by name",
+                        origin, import
+                    )));
+                }
+            }
+        }
+
+        build_imports.extend(items);
     }
 
-    toml_dependencies
+    Ok(build_imports)
+}
+
+/// Generates the additional `lib.rs` code required by the kinds of the tasks used in the
+/// workflow
+pub fn get_kind_library_code(workflow: &Workflow, task_kinds: &TaskKinds) -> String {
+    get_common_kind(workflow)
+        .iter()
+        .filter_map(|kind| task_kinds.get(kind))
+        .map(|kind| kind.library())
+        .collect()
 }
 
-pub fn get_common_kind(workflow: &Workflow) -> HashSet<String> {
-    let mut kinds = HashSet::new();
+pub fn get_common_kind(workflow: &Workflow) -> IndexSet<String> {
+    let mut kinds = IndexSet::new();
     for task in workflow.tasks.values() {
         kinds.insert(task.kind.to_lowercase());
     }
     kinds
 }
 
-pub fn handle_multiple_kinds() -> String {
-    let openwhisk = get_openwhisk();
-    let polkadot = get_polkadot();
-
-    let combined_dependencies = format!("{}{}", openwhisk, polkadot);
-    combined_dependencies
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Compares the generated code with the expected tokens
+    fn assert_tokens(output: TokenStream, expected: TokenStream) {
+        assert_eq!(output.to_string(), expected.to_string());
+    }
+
     #[test]
-    fn test_get_main_method_code_template() {
-        let output = get_main_method_code_template(4);
+    fn test_get_main_method_code() {
+        let output = get_main_method_code(4, quote!(Ok(Value::Null)));
+
+        assert_tokens(
+            output,
+            quote! {
+                #[allow(dead_code, unused)]
+                pub fn main(args: Value) -> Result<Value, String> {
+                    const LIMIT: usize = 4;
+                    let mut workflow = WorkflowGraph::new(LIMIT);
+                    let input: Input = serde_json::from_value(args)
+                        .map_err(|e| record_error(None, WorkflowErrorKind::Input, e.to_string()))?;
+                    Ok(Value::Null)
+                }
+            },
+        );
+    }
 
+    #[test]
+    fn test_get_rust_ident() {
+        assert_eq!(get_rust_ident("task_0").unwrap().to_string(), "task_0");
+        assert_eq!(get_rust_ident("type").unwrap().to_string(), "r#type");
         assert_eq!(
-            &output,
-            "#[allow(dead_code, unused)]
-pub fn main(args: Value) -> Result<Value, String> {
-    const LIMIT: usize = 4;
-    let mut workflow = WorkflowGraph::new(LIMIT);
-    let input: Input = serde_json::from_value(args).map_err(|e| e.to_string())?;
-"
+            get_rust_ident("Self").unwrap_err().to_string(),
+            "`Self` is a Rust keyword which cannot be used as a name"
         );
+        assert_eq!(
+            get_rust_ident("my-task").unwrap_err().to_string(),
+            "`my-task` is not a valid Rust identifier"
+        );
+        assert!(validate_task_name("my-task").is_ok());
+        assert!(validate_task_name("type").is_ok());
+        assert!(validate_task_name("self").is_err());
+        assert!(validate_task_name("--").is_err());
     }
 
     #[test]
     fn test_get_attributes() {
-        let mut attributes = HashMap::new();
+        let mut attributes = IndexMap::new();
         attributes.insert("key".to_string(), "value".to_string());
 
         let output = get_attributes(&attributes);
-        assert_eq!(output, "[Key:\"value\"]");
-      
+        assert_tokens(output.unwrap(), quote!([Key: "value"]));
+
+        // The values are escaped as string literals
+        attributes.insert("quoted".to_string(), "say \"hi\"\\".to_string());
+        let output = get_attributes(&attributes).unwrap();
+        assert_tokens(output, quote!([Key: "value", Quoted: "say \"hi\"\\"]));
+
+        attributes.insert("type".to_string(), "keyword".to_string());
+        assert!(get_attributes(&attributes).is_ok());
+
+        attributes.insert("2fa".to_string(), "digit".to_string());
+        assert_eq!(
+            get_attributes(&attributes).unwrap_err().to_string(),
+            "`2Fa` is not a valid Rust identifier"
+        );
     }
 
     #[test]
@@ -689,7 +1191,7 @@ pub fn main(args: Value) -> Result<Value, String> {
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task1".to_string(), task1);
 
         let workflow = Workflow {
@@ -700,13 +1202,19 @@ pub fn main(args: Value) -> Result<Value, String> {
 
         let output = get_default_value_functions_code(&workflow);
 
-        assert_eq!(
-        output,
-        "\
-pub fn argument_1_fn() -> String{\"value_x\".to_string()}
-pub fn argument_2_fn() -> Vec<String>{let val = serde_json::from_str::<Vec<String>>(\"[\\\"val1,\\\"val2\\\"]\").unwrap();val}
-"
-    )
+        assert_tokens(
+            output.unwrap(),
+            quote! {
+                pub fn argument_1_fn() -> String {
+                    "value_x".to_string()
+                }
+                pub fn argument_2_fn() -> Vec<String> {
+                    // The type is spliced, so its closing `>` stays apart from the turbofish one
+                    let val = serde_json::from_str::<Vec<String> >("[\"val1,\"val2\"]").unwrap();
+                    val
+                }
+            },
+        );
     }
 
     #[test]
@@ -763,7 +1271,7 @@ pub fn argument_2_fn() -> Vec<String>{let val = serde_json::from_str::<Vec<Strin
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
 
         let workflow = Workflow {
@@ -772,26 +1280,45 @@ pub fn argument_2_fn() -> Vec<String>{let val = serde_json::from_str::<Vec<Strin
             tasks,
         };
 
-        let mut custom_types = HashMap::new();
+        let mut custom_types = IndexMap::new();
 
         custom_types.insert(
-        "Struct1".to_string(),
-        "make_input_struct!(\nStruct1,\n{field1:i32},\n[Default, Clone, Debug, Deserialize, Serialize]\n);".to_string());
+            "Struct1".to_string(),
+            "make_input_struct!(Struct1, [field1:i32], [Default, Clone, Debug, Deserialize, Serialize]);"
+                .to_string(),
+        );
 
         let output = get_task_common_input_type_constructor(&custom_types, &workflow);
-        assert_eq!(
-        &output.unwrap(),
-        "\
-make_input_struct!(
-Struct1,
-{field1:i32},
-[Default, Clone, Debug, Deserialize, Serialize]
-);
-make_input_struct!(
-Input,
-[argument_2:i32,argument_3:Vec<u32>,argument_5:String,argument_6:HashMap<i32,f32>,argument_7:(i32,f32),argument_8:Struct1],
-[Debug, Clone, Default, Serialize, Deserialize]
-);")
+        assert_tokens(
+            output.unwrap(),
+            quote! {
+                make_input_struct!(
+                    Struct1,
+                    [field1: i32],
+                    [Default, Clone, Debug, Deserialize, Serialize]
+                );
+                make_input_struct!(
+                    Input,
+                    [
+                        argument_2: i32,
+                        argument_3: Vec<u32>,
+                        argument_5: String,
+                        argument_6: HashMap<i32, f32>,
+                        argument_7: (i32, f32),
+                        argument_8: Struct1
+                    ],
+                    [Debug, Clone, Default, Serialize, Deserialize]
+                );
+            },
+        );
+
+        custom_types.insert("Struct1".to_string(), "make_input_struct!(".to_string());
+        assert!(
+            get_task_common_input_type_constructor(&custom_types, &workflow)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid Rust code for the type Struct1")
+        );
     }
 
     #[test]
@@ -813,7 +1340,7 @@ Input,
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
 
         let workflow = Workflow {
@@ -824,9 +1351,11 @@ Input,
 
         let output = get_task_type_constructors(&workflow);
 
-        assert_eq!(
-            output,
-            "let task_0 = Task0::new(input.argument_1,input.argument_2,\"task0\".to_string());\n"
+        assert_tokens(
+            output.unwrap(),
+            quote! {
+                let task_0 = Task0::new(input.argument_1, input.argument_2, "task0".to_string());
+            },
         );
     }
 
@@ -849,7 +1378,7 @@ Input,
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
 
         let workflow = Workflow {
@@ -860,15 +1389,15 @@ Input,
 
         let output = get_task_input_type_constructors(&workflow);
 
-        println!("{:?}", output);
-
-        assert_eq!(
-            output,
-            "make_input_struct!(
-Task0Input,
-[argument_1:bool,argument_2:i32],
-[Debug, Clone, Default, Serialize, Deserialize]
-);"
+        assert_tokens(
+            output.unwrap(),
+            quote! {
+                make_input_struct!(
+                    Task0Input,
+                    [argument_1: bool, argument_2: i32],
+                    [Debug, Clone, Default, Serialize, Deserialize]
+                );
+            },
         );
     }
 
@@ -892,9 +1421,10 @@ Task0Input,
             ..Default::default()
         };
 
-        let output = get_independent_fields(&task0);
+        let output = get_independent_fields(&task0).unwrap();
 
-        assert_eq!(output, vec!["argument_2:i32"]);
+        assert_eq!(output.len(), 1);
+        assert_tokens(output[0].clone(), quote!(argument_2: i32));
     }
 
     #[test]
@@ -917,7 +1447,7 @@ Task0Input,
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
 
         let workflow = Workflow {
@@ -926,24 +1456,24 @@ Task0Input,
             tasks,
         };
 
-        let output = get_task_main_type_constructors(&workflow);
+        let output = get_task_main_type_constructors(&workflow, &TaskKinds::default());
 
-        assert_eq!(
+        assert_tokens(
             output.unwrap(),
-            "
-make_main_struct!(
-    Task0,
-    Task0Input,
-    [Debug, Clone, Default, Serialize, Deserialize, OpenWhisk],
-    [],
-    output
-);
-impl_new!(
-    Task0,
-    Task0Input,
-    [argument_1:bool,argument_2:i32]
-);
-"
+            quote! {
+                make_main_struct!(
+                    Task0,
+                    Task0Input,
+                    [Debug, Clone, Default, Serialize, Deserialize, OpenWhisk],
+                    [],
+                    output
+                );
+                impl_new!(
+                    Task0,
+                    Task0Input,
+                    [argument_1: bool, argument_2: i32]
+                );
+            },
         );
     }
 
@@ -973,7 +1503,7 @@ impl_new!(
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
 
         let workflow = Workflow {
@@ -984,9 +1514,65 @@ impl_new!(
 
         let output = get_impl_setters_code(&workflow);
 
-        assert_eq!(
+        assert_tokens(
             output.unwrap(),
-            "impl_setter!(Task0, [argument_1:\"data_field\"]);\n"
+            quote!(impl_setter!(Task0, [argument_1: "data_field" => "task1"]);),
+        );
+    }
+
+    #[test]
+    fn test_get_impl_setters_code_with_typed_output() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            kind: "Openwhisk".to_string(),
+            output: IndexMap::from([("data_field".to_string(), RustType::Boolean)]),
+            ..Default::default()
+        };
+
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            kind: "Openwhisk".to_string(),
+            input_arguments: vec![Input {
+                name: "argument_1".to_string(),
+                input_type: RustType::Boolean,
+                is_depend: true,
+                ..Default::default()
+            }],
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                cur_field: "argument_1".to_string(),
+                prev_field: "data_field".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let mut tasks = IndexMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+        };
+
+        assert_tokens(
+            get_task_output_type_constructors(&workflow).unwrap(),
+            quote! {
+                make_input_struct!(
+                    Task0Output,
+                    [pub data_field: bool],
+                    [Debug, Clone, Default, Serialize, Deserialize]
+                );
+            },
+        );
+
+        assert_tokens(
+            get_impl_setters_code(&workflow).unwrap(),
+            quote! {
+                impl_setter!(Task0, []);
+                impl_setter!(Task1, [argument_1: data_field as Task0Output => "task0"]);
+            },
         );
     }
 
@@ -1004,7 +1590,7 @@ impl_new!(
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
         tasks.insert("task1".to_string(), task1);
 
@@ -1015,10 +1601,7 @@ impl_new!(
         };
 
         let output = get_impl_execute_trait_code(&workflow);
-        assert!(
-            output == "impl_execute_trait!(Task0,Task1);"
-                || output == "impl_execute_trait!(Task1,Task0);"
-        );
+        assert_tokens(output.unwrap(), quote!(impl_execute_trait!(Task0, Task1);));
     }
 
     #[test]
@@ -1033,15 +1616,15 @@ impl_new!(
 
         let output = get_add_nodes_code(&flow);
 
-        assert_eq!(
-            output,
-            "\
-let task_0_index = workflow.add_node(Box::new(task_0));
-let task_2_index = workflow.add_node(Box::new(task_2));
-let task_1_index = workflow.add_node(Box::new(task_1));
-let task_4_index = workflow.add_node(Box::new(task_4));
-let task_3_index = workflow.add_node(Box::new(task_3));
-"
+        assert_tokens(
+            output.unwrap(),
+            quote! {
+                let task_0_index = workflow.add_node(Box::new(task_0));
+                let task_2_index = workflow.add_node(Box::new(task_2));
+                let task_1_index = workflow.add_node(Box::new(task_1));
+                let task_4_index = workflow.add_node(Box::new(task_4));
+                let task_3_index = workflow.add_node(Box::new(task_3));
+            },
         )
     }
 
@@ -1099,7 +1682,7 @@ let task_3_index = workflow.add_node(Box::new(task_3));
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
         tasks.insert("task1".to_string(), task1);
         tasks.insert("task2".to_string(), task2);
@@ -1112,21 +1695,22 @@ let task_3_index = workflow.add_node(Box::new(task_3));
             tasks,
         };
 
-        let flow = workflow.get_flow();
+        let flow = workflow.get_flow().unwrap();
 
         let output = get_add_edges_code(&workflow, &flow);
 
-        assert_eq!(
+        assert_tokens(
             output.unwrap(),
-            "\
-workflow.add_edges(&[
-(task_0_index, task_1_index),
-(task_1_index, task_2_index),
-(task_0_index, task_2_index),
-(task_2_index, task_3_index),
-(task_3_index, task_4_index),
-(task_2_index, task_4_index),
-]);"
+            quote! {
+                workflow.add_edges(&[
+                    (task_0_index, task_1_index),
+                    (task_1_index, task_2_index),
+                    (task_0_index, task_2_index),
+                    (task_2_index, task_3_index),
+                    (task_3_index, task_4_index),
+                    (task_2_index, task_4_index)
+                ]);
+            },
         );
     }
 
@@ -1184,7 +1768,7 @@ workflow.add_edges(&[
             ..Default::default()
         };
 
-        let mut tasks = HashMap::new();
+        let mut tasks = IndexMap::new();
         tasks.insert("task0".to_string(), task0);
         tasks.insert("task1".to_string(), task1);
         tasks.insert("task2".to_string(), task2);
@@ -1197,20 +1781,21 @@ workflow.add_edges(&[
             tasks,
         };
 
-        let flow = workflow.get_flow();
+        let flow = workflow.get_flow().unwrap();
 
         let output = get_add_execute_workflow_code(&workflow, &flow);
 
-        assert_eq!(
+        assert_tokens(
             output.unwrap(),
-            "\
-let result = workflow
-.init()?
-.pipe(task_1_index)?
-.pipe(task_2_index)?
-.pipe(task_3_index)?
-.pipe(task_4_index)?
-.term(None)?;"
+            quote! {
+                let result = workflow
+                    .init()?
+                    .pipe(task_1_index)?
+                    .pipe(task_2_index)?
+                    .pipe(task_3_index)?
+                    .pipe(task_4_index)?
+                    .term(None)?;
+            },
         );
     }
 }
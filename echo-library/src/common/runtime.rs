@@ -0,0 +1,217 @@
+use super::*;
+use std::path::Path;
+use std::sync::Arc;
+use wasmtime::{Caller, Engine, Instance, Linker, Store};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::WasiCtxBuilder;
+
+/// State of the store a workflow module runs in
+struct HostState {
+    wasi: WasiP1Ctx,
+    mocks: Arc<MockResponses>,
+    /// Pointer and content of the buffer passed by the module to `set_output`
+    output: Option<(u32, Vec<u8>)>,
+}
+
+/// Host side of the ABI of the wasm module generated for a workflow
+///
+/// The host writes the JSON input into a region allocated with the `memory_alloc` export
+/// and calls `_start` with it. The module hands the output envelope back through the
+/// `host.set_output` import, giving the ownership of its buffer to the host, which copies
+/// it and releases both regions with the `free_memory` export.
+pub struct WorkflowRuntime {
+    engine: Engine,
+    module: wasmtime::Module,
+    mocks: Arc<MockResponses>,
+}
+
+impl WorkflowRuntime {
+    /// Compiles a workflow module
+    ///
+    /// # Arguments
+    ///
+    /// * `wasm` - The content of the module, either in the binary or the text format
+    ///
+    pub fn new(wasm: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let engine = Engine::default();
+        let module = wasmtime::Module::new(&engine, wasm)?;
+
+        Ok(WorkflowRuntime {
+            engine,
+            module,
+            mocks: Arc::default(),
+        })
+    }
+
+    /// Compiles the workflow module built into the given file
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let engine = Engine::default();
+        let module = wasmtime::Module::from_file(&engine, path)
+            .map_err(|err| Error::msg(format!("Failed to load {}: {}", path.display(), err)))?;
+
+        Ok(WorkflowRuntime {
+            engine,
+            module,
+            mocks: Arc::default(),
+        })
+    }
+
+    /// Serves the output of the tasks of the `mock` kind from the given responses
+    pub fn with_mocks(self, mocks: MockResponses) -> Self {
+        WorkflowRuntime {
+            mocks: Arc::new(mocks),
+            ..self
+        }
+    }
+
+    /// Runs the workflow on the given JSON input
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input of the workflow, holding the values of its arguments
+    ///
+    /// # Returns
+    ///
+    /// * `Result<serde_json::Value, Error>` - The output envelope of the workflow, or an
+    ///   error if the module traps or breaks the ABI
+    ///
+    pub fn run(&self, input: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let output = self.call(&serde_json::to_vec(input)?)?;
+
+        serde_json::from_slice(&output)
+            .map_err(|err| Error::msg(format!("Invalid output from the workflow: {}", err)))
+    }
+
+    /// Passes the raw input to `_start` and returns the raw buffer passed to `set_output`
+    pub fn call(&self, input: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                wasi: WasiCtxBuilder::new().inherit_stdio().build_p1(),
+                mocks: self.mocks.clone(),
+                output: None,
+            },
+        );
+
+        let instance = self.linker()?.instantiate(&mut store, &self.module)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| Error::msg("The workflow does not export its memory"))?;
+        let memory_alloc =
+            instance.get_typed_func::<(u32, u32), u32>(&mut store, "memory_alloc")?;
+        let start = instance.get_typed_func::<(u32, u32), ()>(&mut store, "_start")?;
+
+        let length = u32::try_from(input.len())
+            .map_err(|_| Error::msg("The input of the workflow exceeds 4 GiB"))?;
+
+        // A zero sized allocation is undefined behaviour on the guest side
+        let input_ptr = memory_alloc.call(&mut store, (length.max(1), 1))?;
+        memory.write(&mut store, input_ptr as usize, input)?;
+
+        start.call(&mut store, (input_ptr, length))?;
+
+        Self::free(&instance, &mut store, input_ptr, length.max(1))?;
+
+        let (output_ptr, output) = store
+            .data_mut()
+            .output
+            .take()
+            .ok_or_else(|| Error::msg("The workflow did not set its output"))?;
+
+        // An empty output is a dangling pointer on the guest side, owning no allocation
+        if !output.is_empty() {
+            Self::free(&instance, &mut store, output_ptr, output.len() as u32)?;
+        }
+
+        Ok(output)
+    }
+
+    fn linker(&self) -> Result<Linker<HostState>, Error> {
+        let mut linker = Linker::new(&self.engine);
+        preview1::add_to_linker_sync(&mut linker, |state: &mut HostState| &mut state.wasi)?;
+
+        linker.func_wrap(
+            "host",
+            "set_output",
+            |mut caller: Caller<'_, HostState>, ptr: u32, size: u32| -> Result<(), Error> {
+                let memory = caller
+                    .get_export("memory")
+                    .and_then(|export| export.into_memory())
+                    .ok_or_else(|| Error::msg("The workflow does not export its memory"))?;
+
+                let mut output = vec![0; size as usize];
+                memory.read(&caller, ptr as usize, &mut output)?;
+
+                if caller.data_mut().output.replace((ptr, output)).is_some() {
+                    return Err(Error::msg("The workflow set its output more than once"));
+                }
+
+                Ok(())
+            },
+        )?;
+
+        linker.func_wrap(
+            "host",
+            "mock_output",
+            |mut caller: Caller<'_, HostState>,
+             action_ptr: u32,
+             action_size: u32,
+             input_ptr: u32,
+             input_size: u32,
+             slot_ptr: u32|
+             -> Result<u32, Error> {
+                let memory = caller
+                    .get_export("memory")
+                    .and_then(|export| export.into_memory())
+                    .ok_or_else(|| Error::msg("The workflow does not export its memory"))?;
+
+                let mut action_name = vec![0; action_size as usize];
+                memory.read(&caller, action_ptr as usize, &mut action_name)?;
+                let mut input = vec![0; input_size as usize];
+                memory.read(&caller, input_ptr as usize, &mut input)?;
+
+                let action_name = String::from_utf8_lossy(&action_name).to_string();
+                let input: serde_json::Value = serde_json::from_slice(&input)?;
+
+                let (status, response) = match caller.data().mocks.response(&action_name, &input) {
+                    Ok(output) => (0, serde_json::to_vec(output)?),
+                    Err(message) => (1, message.into_bytes()),
+                };
+
+                let size = response.len() as u32;
+                let ptr = match size {
+                    0 => 0,
+                    size => caller
+                        .get_export("memory_alloc")
+                        .and_then(|export| export.into_func())
+                        .ok_or_else(|| Error::msg("The workflow does not export memory_alloc"))?
+                        .typed::<(u32, u32), u32>(&caller)?
+                        .call(&mut caller, (size, 1))?,
+                };
+
+                memory.write(&mut caller, ptr as usize, &response)?;
+
+                let mut slot = ptr.to_le_bytes().to_vec();
+                slot.extend(size.to_le_bytes());
+                memory.write(&mut caller, slot_ptr as usize, &slot)?;
+
+                Ok(status)
+            },
+        )?;
+
+        Ok(linker)
+    }
+
+    fn free(
+        instance: &Instance,
+        store: &mut Store<HostState>,
+        ptr: u32,
+        size: u32,
+    ) -> Result<(), Error> {
+        let free_memory =
+            instance.get_typed_func::<(u32, u32, u32), ()>(&mut *store, "free_memory")?;
+
+        free_memory.call(store, (ptr, size, 1))
+    }
+}
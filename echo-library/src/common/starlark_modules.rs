@@ -1,6 +1,6 @@
 use super::*;
 use anyhow::anyhow;
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 #[starlark_module]
 pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// Creates a new task of the workflow and returns a task object of `Task` type
@@ -15,6 +15,9 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// * `operation` - An optional argument to mention type of the task operation
     /// * `depend_on` - The dependencies of the task
     ///   (i.e "map", "concat")
+    /// * `output` - An optional dictionary of the output fields of the task and their types,
+    ///   used to check the fields the dependent tasks take from it
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
     ///
     /// # Returns
     ///
@@ -27,42 +30,184 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         attributes: Option<Value>,
         operation: Option<Value>,
         depend_on: Option<Value>,
+        output: Option<Value>,
+        eval: &mut Evaluator,
     ) -> anyhow::Result<Task> {
-        if kind == "openwhisk" || kind == "polkadot" {
-            if attributes.is_none() {
-                return Err(anyhow!(
-                    "Attributes are mandatory for kind: openwhisk or polkadot"
-                ));
-            }
-        }
+        let composer = eval
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.downcast_ref::<Composer>())
+            .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?;
 
-        let mut input_arguments: Vec<Input> = serde_json::from_str(&input_arguments.to_json()?)
-            .map_err(|err| anyhow!("Failed to parse input arguments: {}", err))?;
+        let location = eval.call_stack_top_location().map(Location::from);
+        let diagnostic = |code: DiagnosticCode, message: String| {
+            Diagnostic::new(code, message)
+                .at(location.clone())
+                .in_task(&action_name)
+        };
 
-        let attributes: HashMap<String, String> = match attributes {
-            Some(attributes) => serde_json::from_str(&attributes.to_json()?)
-                .map_err(|err| anyhow!("Failed to parse the attributes: {}", err))?,
-            _ => HashMap::default(),
+        let has_attributes = attributes.is_some();
+
+        let mut input_arguments: Vec<Input> = serde_json::from_str(&input_arguments.to_json()?)
+            .map_err(|err| {
+                diagnostic(
+                    DiagnosticCode::InvalidArgument,
+                    format!("Failed to parse input arguments: {}", err),
+                )
+                .with_hint("`input_arguments` must be a list of `argument(...)` values")
+            })?;
+
+        let attributes: IndexMap<String, String> = match attributes {
+            Some(attributes) => serde_json::from_str(&attributes.to_json()?).map_err(|err| {
+                diagnostic(
+                    DiagnosticCode::InvalidAttribute,
+                    format!("Failed to parse the attributes: {}", err),
+                )
+                .with_hint("`attributes` must be a dictionary of string keys and string values")
+            })?,
+            _ => IndexMap::default(),
         };
 
         let depend_on: Vec<Depend> = match depend_on {
-            Some(val) => serde_json::from_str(&val.to_json()?)
-                .map_err(|err| anyhow!("Failed to parse depend-on: {}", err))?,
+            Some(val) => serde_json::from_str(&val.to_json()?).map_err(|err| {
+                diagnostic(
+                    DiagnosticCode::InvalidArgument,
+                    format!("Failed to parse depend-on: {}", err),
+                )
+                .with_hint("`depend_on` must be a list of `depend(...)` values")
+            })?,
             None => Vec::default(),
         };
 
+        let output: IndexMap<String, RustType> = match output {
+            Some(output) => serde_json::from_str(&output.to_json()?).map_err(|err| {
+                diagnostic(
+                    DiagnosticCode::InvalidArgument,
+                    format!("Failed to parse the task output: {}", err),
+                )
+                .with_hint("`output` must be a dictionary of field names and types")
+            })?,
+            None => IndexMap::default(),
+        };
+
+        if let Err(err) = validate_task_name(&action_name) {
+            composer.report(
+                diagnostic(DiagnosticCode::InvalidIdentifier, err.to_string()).with_hint(
+                    "the name of a task must start with a letter and is generated as the name of its Rust type and variable",
+                ),
+            );
+        }
+
+        let mut argument_names = HashSet::new();
+
+        for argument in input_arguments.iter() {
+            if !argument_names.insert(argument.name.as_str()) {
+                composer.report(
+                    diagnostic(
+                        DiagnosticCode::InvalidIdentifier,
+                        format!(
+                            "Duplicate argument `{}`, argument names must be unique",
+                            argument.name
+                        ),
+                    )
+                    .with_hint("rename one of the arguments of the task"),
+                );
+            }
+        }
+
+        for field in output.keys() {
+            if let Err(err) = get_rust_ident(field) {
+                composer.report(
+                    diagnostic(
+                        DiagnosticCode::InvalidIdentifier,
+                        format!("Invalid output field: {}", err),
+                    )
+                    .with_hint(
+                        "the output fields of a task are generated as the fields of a Rust struct",
+                    ),
+                );
+            }
+        }
+
+        let task_kinds = composer.task_kinds.borrow();
+
+        match task_kinds.get(&kind) {
+            None => composer.report(
+                diagnostic(
+                    DiagnosticCode::UnknownKind,
+                    format!(
+                        "Unsupported task kind: {}, expected one of: {}",
+                        kind,
+                        task_kinds.names().join(", ")
+                    ),
+                )
+                .with_hint(
+                    "register the kind with `Composer::add_task_kind` or declare it in composer.toml",
+                ),
+            ),
+            Some(task_kind) => {
+                let has_required_attributes = task_kind
+                    .attributes()
+                    .iter()
+                    .any(|attribute| attribute.required);
+
+                if has_required_attributes && !has_attributes {
+                    composer.report(diagnostic(
+                        DiagnosticCode::InvalidAttribute,
+                        format!("Attributes are mandatory for kind: {}", kind),
+                    ));
+                } else {
+                    for diagnostic in task_kind.validate_attributes(&attributes) {
+                        composer.report(diagnostic.at(location.clone()).in_task(&action_name));
+                    }
+                }
+            }
+        }
+
         for depend in depend_on.iter() {
-            for argument in input_arguments.iter_mut() {
-                if argument.name == depend.cur_field {
-                    argument.is_depend = true;
-                    break;
+            match input_arguments
+                .iter_mut()
+                .find(|argument| argument.name == depend.cur_field)
+            {
+                Some(argument) => argument.is_depend = true,
+                None => {
+                    let names: Vec<&str> = input_arguments
+                        .iter()
+                        .map(|argument| argument.name.as_str())
+                        .collect();
+
+                    let hint = if names.is_empty() {
+                        "`cur_field` must be an input argument of the task, which declares none"
+                            .to_string()
+                    } else {
+                        format!(
+                            "`cur_field` must be one of the input arguments of the task: {}",
+                            names.join(", ")
+                        )
+                    };
+
+                    composer.report(
+                        diagnostic(
+                            DiagnosticCode::UnknownField,
+                            format!(
+                                "Unknown field `{}` in the dependency on task '{}'",
+                                depend.cur_field, depend.task_name
+                            ),
+                        )
+                        .with_hint(hint),
+                    );
                 }
             }
         }
 
         let operation: Operation = match operation {
-            Some(op) => serde_json::from_str(&op.to_json()?)
-                .map_err(|err| anyhow!("Failed to parse the task operation value: {}", err))?,
+            Some(op) => serde_json::from_str(&op.to_json()?).map_err(|err| {
+                diagnostic(
+                    DiagnosticCode::InvalidArgument,
+                    format!("Failed to parse the task operation value: {}", err),
+                )
+                .with_hint("use one of `Operation.normal()`, `Operation.concat()`, `Operation.combine()` or `Operation.map(field)`")
+            })?,
             _ => Operation::Normal,
         };
 
@@ -73,6 +218,8 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
             attributes,
             operation,
             depend_on,
+            output,
+            location,
         })
     }
 
@@ -97,31 +244,66 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         tasks: Value,
         eval: &mut Evaluator,
     ) -> anyhow::Result<Workflow> {
-        let tasks: Vec<Task> = serde_json::from_str(&tasks.to_json()?)
-            .map_err(|err| anyhow!("Failed to parse task value: {}", err))?;
+        let tasks: Vec<Task> = serde_json::from_str(&tasks.to_json()?).map_err(|err| {
+            Diagnostic::new(
+                DiagnosticCode::InvalidArgument,
+                format!("Failed to parse task value: {}", err),
+            )
+            .in_workflow(&name)
+            .with_hint("`tasks` must be a list of `task(...)` values")
+        })?;
 
-        let mut task_hashmap = HashMap::new();
+        let mut task_hashmap = IndexMap::new();
+
+        let composer = eval
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.downcast_ref::<Composer>())
+            .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?;
 
         for task in tasks {
             if task_hashmap.contains_key(&task.action_name) {
-                return Err(Error::msg("Duplicate tasks, Task names must be unique"));
+                composer.report(
+                    Diagnostic::new(
+                        DiagnosticCode::DuplicateTask,
+                        "Duplicate tasks, Task names must be unique",
+                    )
+                    .at(task.location.clone())
+                    .in_workflow(&name)
+                    .in_task(&task.action_name)
+                    .with_hint(format!(
+                        "rename one of the tasks with the action name '{}'",
+                        task.action_name
+                    )),
+                );
             } else {
                 task_hashmap.insert(task.action_name.clone(), task);
             }
         }
 
-        eval.extra
-            .as_ref()
-            .and_then(|extra| extra.downcast_ref::<Composer>())
-            .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?
-            .add_workflow(name.clone(), version.clone(), task_hashmap.clone())
-            .map_err(|err| anyhow!("Failed to add workflow: {}", err))?;
-
-        Ok(Workflow {
+        let workflow = Workflow {
             name,
             version,
             tasks: task_hashmap,
-        })
+        };
+
+        for diagnostic in workflow.check_names(
+            &composer.custom_types.borrow(),
+            &composer.task_kinds.borrow(),
+        ) {
+            composer.report(diagnostic);
+        }
+
+        if let Err(err) = composer.add_workflow(
+            workflow.name.clone(),
+            workflow.version.clone(),
+            workflow.tasks.clone(),
+        ) {
+            let location = eval.call_stack_top_location().map(Location::from);
+            composer.report(err.downcast::<Diagnostic>()?.at(location));
+        }
+
+        Ok(workflow)
     }
 
     /// Creates a new field for the input argument of a task
@@ -140,9 +322,35 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         name: String,
         input_type: Value,
         default_value: Option<Value>,
+        eval: &mut Evaluator,
     ) -> anyhow::Result<Input> {
-        let input_type: RustType = serde_json::from_str(&input_type.to_json()?)
-            .map_err(|err| anyhow!("Failed to parse input arguments: {}", err))?;
+        let composer = eval
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.downcast_ref::<Composer>())
+            .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?;
+
+        if let Err(err) = get_rust_ident(&name) {
+            composer.report(
+                Diagnostic::new(
+                    DiagnosticCode::InvalidIdentifier,
+                    format!("Invalid argument name: {}", err),
+                )
+                .at(eval.call_stack_top_location().map(Location::from))
+                .with_hint("the arguments of a task are generated as the fields of a Rust struct"),
+            );
+        }
+
+        let input_type: RustType = serde_json::from_str(&input_type.to_json()?).map_err(|err| {
+            Diagnostic::new(
+                DiagnosticCode::InvalidArgument,
+                format!("Failed to parse input arguments: {}", err),
+            )
+            .with_hint(format!(
+                "the type of the argument `{}` must be one of Int, Uint, Float, String, Bool, List, HashMap, Tuple or an EchoStruct",
+                name
+            ))
+        })?;
 
         let default_value: Option<String> = match default_value {
             Some(value) => {
@@ -150,42 +358,49 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
                     .to_json()
                     .map_err(|err| anyhow!("Failed to parse default value: {}", err))?;
 
-                match input_type {
-                    RustType::String => {
-                        if !value_str.contains("\"") {
-                            return Err(anyhow!("Value must be in String type"));
-                        }
+                let mismatch = match input_type {
+                    RustType::String if !value_str.contains('"') => {
+                        Some("Value must be in String type")
                     }
-                    RustType::Int => {
-                        if value_str.parse::<i32>().is_err() {
-                            return Err(anyhow!("Value must be an integer"));
-                        }
+                    RustType::Int if value_str.parse::<i32>().is_err() => {
+                        Some("Value must be an integer")
                     }
-                    RustType::Float => {
-                        if value_str.parse::<f32>().is_err() {
-                            return Err(anyhow!("Value must be a float"));
-                        }
+                    RustType::Float if value_str.parse::<f32>().is_err() => {
+                        Some("Value must be a float")
                     }
-                    RustType::Uint => {
-                        if value_str.parse::<u32>().is_err() {
-                            return Err(anyhow!("Value must be a positive integer"));
-                        }
+                    RustType::Uint if value_str.parse::<u32>().is_err() => {
+                        Some("Value must be a positive integer")
                     }
-                    RustType::Boolean => {
-                        if value_str != "true" && value_str != "false" {
-                            return Err(anyhow!("Value must be either true or false"));
-                        }
+                    RustType::Boolean if value_str != "true" && value_str != "false" => {
+                        Some("Value must be either true or false")
                     }
-                    RustType::HashMap(_, _) => {}
-                    RustType::List(_) => {}
-                    RustType::Tuple(_, _) => {}
-                    RustType::Struct(_) => {}
-                    _ => {
-                        return Err(anyhow!("Unsupported input type for default value"));
+                    RustType::String
+                    | RustType::Int
+                    | RustType::Float
+                    | RustType::Uint
+                    | RustType::Boolean
+                    | RustType::HashMap(_, _)
+                    | RustType::List(_)
+                    | RustType::Tuple(_, _)
+                    | RustType::Struct(_) => None,
+                    _ => Some("Unsupported input type for default value"),
+                };
+
+                match mismatch {
+                    Some(message) => {
+                        composer.report(
+                            Diagnostic::new(DiagnosticCode::TypeMismatch, message)
+                                .at(eval.call_stack_top_location().map(Location::from))
+                                .with_hint(format!(
+                                    "change the default value `{}` of the argument `{}` to match its type `{}`",
+                                    value_str, name, input_type
+                                )),
+                        );
+
+                        None
                     }
+                    None => Some(value_str),
                 }
-
-                Some(value_str)
             }
             None => Default::default(),
         };
@@ -220,34 +435,101 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// * A Result containing the name of the user-defined type
     ///
     fn EchoStruct(name: String, fields: Value, eval: &mut Evaluator) -> anyhow::Result<RustType> {
-        let fields: HashMap<String, RustType> = serde_json::from_str(&fields.to_json()?)
-            .map_err(|err| anyhow!("Failed to parse fields: {}", err))?;
+        let fields: IndexMap<String, RustType> =
+            serde_json::from_str(&fields.to_json()?).map_err(|err| {
+                Diagnostic::new(
+                    DiagnosticCode::InvalidArgument,
+                    format!("Failed to parse fields: {}", err),
+                )
+                .with_hint(format!(
+                    "the fields of `{}` must be a dictionary of field names and types",
+                    name
+                ))
+            })?;
 
         let composer = eval
             .extra
             .as_ref()
             .and_then(|extra| extra.downcast_ref::<Composer>())
             .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?;
-        let name = name.to_case(Case::Pascal);
+        let declared_name = name;
+        let name = declared_name.to_case(Case::Pascal);
+
+        let location = eval.call_stack_top_location().map(Location::from);
+        let mut errors = Vec::new();
+
+        if RESERVED_NAMES.contains(&name.as_str()) {
+            errors.push(Error::msg(format!(
+                "`{}` is reserved by the generated code",
+                name
+            )));
+        }
 
-        let mut build_string = Vec::new();
+        let struct_name = get_rust_ident(&name).map_err(|err| errors.push(err)).ok();
+        let mut build_fields = Vec::new();
 
         for (key, value) in fields {
-            build_string.push(format!("{}:{}", key, value));
+            match (get_rust_ident(&key), get_rust_type(&value)) {
+                (Ok(key), Ok(value)) => build_fields.push(quote::quote!(#key: #value)),
+                (key, value) => errors.extend(key.err().into_iter().chain(value.err())),
+            }
         }
 
-        let build_string = format!("[{}]", build_string.join(","));
+        let struct_name = match struct_name {
+            Some(struct_name) if errors.is_empty() => struct_name,
+            _ => {
+                for err in errors {
+                    composer.report(
+                        Diagnostic::new(DiagnosticCode::InvalidIdentifier, err.to_string())
+                            .at(location.clone())
+                            .with_hint(format!(
+                                "the names of `{}` and its fields are generated as Rust identifiers",
+                                name
+                            )),
+                    );
+                }
+
+                // Declared without code, so that the tasks using it are not reported as
+                // missing a custom type
+                composer
+                    .custom_types
+                    .borrow_mut()
+                    .entry(name.clone())
+                    .or_default();
+
+                return Ok(RustType::Struct(name));
+            }
+        };
 
-        composer
-            .custom_types
-            .borrow_mut()
-            .insert(
+        let mut custom_types = composer.custom_types.borrow_mut();
+
+        // The types of the EchoStructs are named by their Pascal case, which the names of
+        // two EchoStructs, such as `my_type` and `MyType`, can share
+        if custom_types.contains_key(&name) {
+            composer.report(
+                Diagnostic::new(
+                    DiagnosticCode::InvalidIdentifier,
+                    format!(
+                        "EchoStruct '{}' is generated as `{}`, the name of another EchoStruct",
+                        declared_name, name
+                    ),
+                )
+                .at(location)
+                .with_hint("rename one of the EchoStructs so that their names differ by more than their case and separators"),
+            );
+        } else {
+            custom_types.insert(
                 name.to_string(),
-                format!(
-                "make_input_struct!(\n{},\n{},\n[Default, Clone, Debug, Deserialize, Serialize]\n);",
-                &name,
-                build_string
-            ));
+                quote::quote! {
+                    make_input_struct!(
+                        #struct_name,
+                        [#(#build_fields),*],
+                        [Default, Clone, Debug, Deserialize, Serialize]
+                    );
+                }
+                .to_string(),
+            );
+        }
 
         Ok(RustType::Struct(name))
     }
@@ -0,0 +1,516 @@
+use super::*;
+
+/// Format expected from the value of an attribute
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeFormat {
+    #[default]
+    Any,
+    /// An `http` or `https` URL with a host
+    Url,
+    /// Either `true` or `false`
+    Bool,
+    /// One of the listed values
+    #[serde(skip)]
+    Enum(Vec<String>),
+}
+
+impl AttributeFormat {
+    /// Checks the value of an attribute against the format
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the attribute as written in the config file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Ok if the value matches the format, otherwise a description
+    ///   of the expected value
+    ///
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let is_valid = match self {
+            AttributeFormat::Any => true,
+            AttributeFormat::Url => ["http://", "https://"].iter().any(|scheme| {
+                value
+                    .strip_prefix(scheme)
+                    .is_some_and(|host| !host.is_empty() && !host.starts_with('/'))
+            }),
+            AttributeFormat::Bool => value == "true" || value == "false",
+            AttributeFormat::Enum(values) => values.iter().any(|allowed| allowed == value),
+        };
+
+        if is_valid {
+            return Ok(());
+        }
+
+        Err(match self {
+            AttributeFormat::Any => String::new(),
+            AttributeFormat::Url => "expected an http(s) URL".to_string(),
+            AttributeFormat::Bool => "expected `true` or `false`".to_string(),
+            AttributeFormat::Enum(values) => format!("expected one of: {}", values.join(", ")),
+        })
+    }
+}
+
+/// Attribute accepted by the tasks of a kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSchema {
+    pub name: String,
+    pub required: bool,
+    pub format: AttributeFormat,
+}
+
+impl AttributeSchema {
+    pub fn required(name: &str) -> Self {
+        AttributeSchema {
+            name: name.to_string(),
+            required: true,
+            format: AttributeFormat::Any,
+        }
+    }
+
+    pub fn optional(name: &str) -> Self {
+        AttributeSchema {
+            name: name.to_string(),
+            required: false,
+            format: AttributeFormat::Any,
+        }
+    }
+
+    /// Restricts the values accepted by the attribute to the given format
+    pub fn with_format(self, format: AttributeFormat) -> Self {
+        AttributeSchema { format, ..self }
+    }
+}
+
+/// Backend executing the tasks of a workflow, i.e "openwhisk" or "polkadot"
+///
+/// A kind bundles everything the code generator needs to emit a task of that kind
+/// in the workflow package. Custom kinds are registered to the composer through
+/// `Composer::add_task_kind` before compiling the config files.
+pub trait TaskKind: fmt::Debug + Send + Sync {
+    /// Name of the kind as used by the `kind` argument of a task inside the config file
+    fn name(&self) -> &str;
+
+    /// Derive macro implementing the execution of the task struct
+    fn derive(&self) -> &str;
+
+    /// `use` statements required by the derive macro inside `types.rs`
+    fn imports(&self) -> Vec<String>;
+
+    /// Dependencies added to the `Cargo.toml` of the workflow package, one per line
+    fn dependencies(&self) -> Vec<String>;
+
+    /// Attributes accepted by the tasks of this kind
+    fn attributes(&self) -> Vec<AttributeSchema> {
+        Vec::new()
+    }
+
+    /// Additional Rust code appended to `lib.rs` of the workflow package
+    fn library(&self) -> String {
+        String::new()
+    }
+
+    /// Whether the tasks of this kind can be built into a WebAssembly component, whose
+    /// host only provides the WASI imports
+    fn supports_component(&self) -> bool {
+        true
+    }
+
+    /// Additional Rust code appended to `types.rs` for every task of this kind
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The name of the struct generated for the task
+    ///
+    fn task_code(&self, _task: &str) -> String {
+        String::new()
+    }
+
+    /// Validates the attributes of a task against the attributes declared by the kind
+    ///
+    /// Kinds declaring no attributes accept any attribute. Otherwise every attribute must
+    /// be declared by the kind, every value must match the format of its attribute and
+    /// every required attribute must be present.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - The attributes of the task as written in the config file
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Diagnostic>` - Every invalid attribute of the task, the unknown ones first so
+    ///   that a misspelled attribute is reported with the name it is close to
+    ///
+    fn validate_attributes(&self, attributes: &IndexMap<String, String>) -> Vec<Diagnostic> {
+        let schema = self.attributes();
+        let mut diagnostics = Vec::new();
+
+        if schema.is_empty() {
+            return diagnostics;
+        }
+
+        for (name, value) in attributes.iter() {
+            let attribute = match schema.iter().find(|attribute| &attribute.name == name) {
+                Some(attribute) => attribute,
+                None => {
+                    let names: Vec<&str> = schema
+                        .iter()
+                        .map(|attribute| attribute.name.as_str())
+                        .collect();
+
+                    let hint = match closest_name(name, &names) {
+                        Some(closest) => format!("did you mean `{}`?", closest),
+                        None => format!("expected one of: {}", names.join(", ")),
+                    };
+
+                    diagnostics.push(
+                        Diagnostic::new(
+                            DiagnosticCode::InvalidAttribute,
+                            format!(
+                                "Unknown attribute `{}` for the task of kind: {}",
+                                name,
+                                self.name()
+                            ),
+                        )
+                        .with_hint(hint),
+                    );
+                    continue;
+                }
+            };
+
+            if let Err(expected) = attribute.format.check(value) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::InvalidAttribute,
+                    format!(
+                        "Invalid value `{}` for attribute `{}` of the task of kind: {}, {}",
+                        value,
+                        name,
+                        self.name(),
+                        expected
+                    ),
+                ));
+            }
+        }
+
+        for attribute in schema.iter().filter(|attribute| attribute.required) {
+            if !attributes.contains_key(&attribute.name) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        DiagnosticCode::InvalidAttribute,
+                        format!(
+                            "Missing attribute `{}` for the task of kind: {}",
+                            attribute.name,
+                            self.name()
+                        ),
+                    )
+                    .with_hint(format!(
+                        "add `{}` to the attributes of the task",
+                        attribute.name
+                    )),
+                );
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Finds the candidate closest to a misspelled name, within an edit distance of two
+fn closest_name<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Registry of the task kinds known to the composer
+#[derive(Debug)]
+pub struct TaskKinds {
+    kinds: IndexMap<String, Box<dyn TaskKind>>,
+}
+
+impl Default for TaskKinds {
+    fn default() -> Self {
+        let mut task_kinds = TaskKinds {
+            kinds: IndexMap::new(),
+        };
+
+        task_kinds.register(OpenWhiskKind);
+        task_kinds.register(PolkadotKind);
+        task_kinds.register(HelloWorldKind);
+        task_kinds.register(MockKind);
+
+        task_kinds
+    }
+}
+
+impl TaskKinds {
+    /// Registers a task kind, replacing any kind previously registered with the same name
+    pub fn register(&mut self, kind: impl TaskKind + 'static) {
+        self.kinds
+            .insert(kind.name().to_lowercase(), Box::new(kind));
+    }
+
+    /// Finds the task kind with the given name, ignoring the case of the name
+    pub fn get(&self, name: &str) -> Option<&dyn TaskKind> {
+        self.kinds
+            .get(&name.to_lowercase())
+            .map(|kind| kind.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.kinds.values().map(|kind| kind.name()).collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct OpenWhiskKind;
+
+impl TaskKind for OpenWhiskKind {
+    fn name(&self) -> &str {
+        "openwhisk"
+    }
+
+    fn derive(&self) -> &str {
+        "OpenWhisk"
+    }
+
+    fn imports(&self) -> Vec<String> {
+        // The items of `openwhisk_rust` are imported by the boilerplate
+        vec!["use openwhisk_macro::OpenWhisk;".to_string()]
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["openwhisk_macro = \"0.1.6\"".to_string()]
+    }
+
+    fn attributes(&self) -> Vec<AttributeSchema> {
+        vec![
+            AttributeSchema::required("api_host").with_format(AttributeFormat::Url),
+            AttributeSchema::required("auth_key"),
+            AttributeSchema::required("namespace"),
+            AttributeSchema::optional("insecure").with_format(AttributeFormat::Bool),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct PolkadotKind;
+
+impl TaskKind for PolkadotKind {
+    fn name(&self) -> &str {
+        "polkadot"
+    }
+
+    fn derive(&self) -> &str {
+        "Polkadot"
+    }
+
+    fn imports(&self) -> Vec<String> {
+        vec![
+            "use substrate_macro::Polkadot;".to_string(),
+            "use sp_core::H256;".to_string(),
+        ]
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec![
+            "substrate_macro = \"0.1.3\"".to_string(),
+            "pallet-staking = { git = \"https://github.com/paritytech/substrate.git\", package = \"pallet-staking\", rev = \"eb1a2a8\" }".to_string(),
+            "substrate-api-client = { git = \"https://github.com/HugoByte/substrate-api-client.git\", default-features = false, features = [\"staking-xt\"], branch =\"wasm-support\"}".to_string(),
+            "sp-core = { version = \"6.0.0\", default-features = false, features = [\"full_crypto\"], git = \"https://github.com/paritytech/substrate.git\", rev = \"eb1a2a8\" }".to_string(),
+            "sp-runtime = { version = \"6.0.0\", default-features = false, git = \"https://github.com/paritytech/substrate.git\", rev = \"eb1a2a8\" }".to_string(),
+        ]
+    }
+
+    fn attributes(&self) -> Vec<AttributeSchema> {
+        vec![
+            AttributeSchema::required("chain").with_format(AttributeFormat::Enum(vec![
+                "westend".to_string(),
+                "polkadot".to_string(),
+                "kusama".to_string(),
+            ])),
+            AttributeSchema::required("operation"),
+        ]
+    }
+
+    fn library(&self) -> String {
+        "\
+use sp_runtime::AccountId32;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub struct StakingLedger {
+    pub stash: AccountId32,
+    #[codec(compact)]
+    pub total: u128,
+    #[codec(compact)]
+    pub active: u128,
+    pub unlocking: Vec<u32>,
+    pub claimed_rewards: Vec<u32>,
+}
+"
+        .to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct HelloWorldKind;
+
+impl TaskKind for HelloWorldKind {
+    fn name(&self) -> &str {
+        "hello_world"
+    }
+
+    fn derive(&self) -> &str {
+        "HelloWorldDerive"
+    }
+
+    fn imports(&self) -> Vec<String> {
+        vec!["use hello_world_macro::HelloWorldDerive;".to_string()]
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["hello_world_macro = \"0.0.1\"".to_string()]
+    }
+}
+
+/// Kind serving the output of its tasks from the mock responses given to the host running
+/// the workflow, so that workflows can be tried without their backends
+///
+/// The tasks ask the host for their output through the `host.mock_output` import, passing
+/// their action name and input. The host allocates the response with `memory_alloc` and
+/// hands its ownership to the task through the pointer and length written into the slot.
+/// Built natively, the tasks read the responses from the file named by `COMPOSER_MOCK`.
+/// The kind cannot be built into a component, which has no such import.
+#[derive(Debug)]
+pub struct MockKind;
+
+impl TaskKind for MockKind {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn derive(&self) -> &str {
+        ""
+    }
+
+    fn imports(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn supports_component(&self) -> bool {
+        false
+    }
+
+    fn library(&self) -> String {
+        let library = "\
+#[cfg(target_arch = \"wasm32\")]
+#[link(wasm_import_module = \"host\")]
+extern \"C\" {
+    fn mock_output(
+        action_ptr: i32,
+        action_size: i32,
+        input_ptr: i32,
+        input_size: i32,
+        slot_ptr: i32,
+    ) -> i32;
+}
+
+/// Asks the host for the output mocked for the action with the given input
+#[cfg(target_arch = \"wasm32\")]
+pub fn mock_task_output(action_name: &str, input: Value) -> Result<Value, String> {
+    let input = serde_json::to_vec(&input).map_err(|err| err.to_string())?;
+    let mut slot = [0u32; 2];
+
+    let status = unsafe {
+        mock_output(
+            action_name.as_ptr() as i32,
+            action_name.len() as i32,
+            input.as_ptr() as i32,
+            input.len() as i32,
+            slot.as_mut_ptr() as i32,
+        )
+    };
+
+    let response = match slot[1] as usize {
+        0 => Vec::new(),
+        size => unsafe { Vec::from_raw_parts(slot[0] as *mut u8, size, size) },
+    };
+
+    if status != 0 {
+        return Err(String::from_utf8_lossy(&response).to_string());
+    }
+
+    serde_json::from_slice(&response).map_err(|err| err.to_string())
+}
+
+/// Reads the output mocked for the action with the given input from the JSON file named
+/// by the `COMPOSER_MOCK` environment variable, when the workflow is built natively
+#[cfg(not(target_arch = \"wasm32\"))]
+pub fn mock_task_output(action_name: &str, input: Value) -> Result<Value, String> {
+    let path = std::env::var(\"COMPOSER_MOCK\")
+        .map_err(|_| \"Set COMPOSER_MOCK to the path of the mock responses\".to_string())?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|err| format!(\"Failed to read {}: {}\", path, err))?;
+    let mocks: Value = serde_json::from_str(&content)
+        .map_err(|err| format!(\"Invalid mock responses {}: {}\", path, err))?;
+
+    mocks
+        .get(action_name)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|response| mock_input_matches(response.get(\"input\"), &input))
+        .and_then(|response| response.get(\"output\"))
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                \"No mock response for the action '{}' with the input {}\",
+                action_name, input
+            )
+        })
+}
+";
+
+        [library, include_str!("mock_input.rs")].join("\n")
+    }
+
+    fn task_code(&self, task: &str) -> String {
+        format!(
+            "\
+impl {task} {{
+    pub fn run(&mut self) -> Result<(), String> {{
+        let input = to_value(&self.input).map_err(|err| err.to_string())?;
+        self.output = mock_task_output(&self.action_name, input)?;
+        Ok(())
+    }}
+}}
+"
+        )
+    }
+}
@@ -0,0 +1,147 @@
+use super::*;
+use std::path::Path;
+
+/// Regression test of a workflow, read from a `tests/<name>.json` file of the package
+///
+/// ```json
+/// {
+///   "workflow": "employee_salary",
+///   "input": { "role": "developer" },
+///   "mocks": { "employee_ids": [{ "output": { "ids": [1, 2] } }] },
+///   "output": { "1": 1000, "2": 2000 }
+/// }
+/// ```
+///
+/// The case passes when the workflow succeeds with the expected `output`, or when it fails
+/// with an error holding every field of the expected `error`. A case declaring neither
+/// only expects the workflow to succeed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    #[serde(skip)]
+    pub name: String,
+    /// Workflow to run, which can be omitted if the package declares a single workflow
+    #[serde(default)]
+    pub workflow: Option<String>,
+    #[serde(default = "empty_input")]
+    pub input: serde_json::Value,
+    #[serde(default)]
+    pub mocks: MockResponses,
+    #[serde(default)]
+    pub output: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<serde_json::Value>,
+}
+
+fn empty_input() -> serde_json::Value {
+    serde_json::Value::Object(Default::default())
+}
+
+impl TestCase {
+    /// Reads a test case, named after its file
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::msg(format!("Failed to read {}: {}", path.display(), err)))?;
+
+        let case: TestCase = serde_json::from_str(&content)
+            .map_err(|err| Error::msg(format!("Invalid test {}: {}", path.display(), err)))?;
+
+        Ok(TestCase {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            ..case
+        })
+    }
+
+    /// Checks the output envelope of the workflow against the expectations of the case
+    ///
+    /// # Arguments
+    ///
+    /// * `envelope` - The output envelope returned by the workflow run on the input of the case
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The differences found, one per line, empty if the case passes
+    ///
+    pub fn check(&self, envelope: &serde_json::Value) -> Vec<String> {
+        let status = envelope["status"].as_str().unwrap_or_default();
+
+        match &self.error {
+            Some(expected) if status == "failure" => {
+                json_diff("$.error", expected, &envelope["error"], true)
+            }
+            Some(_) => vec![format!(
+                "$.status: expected \"failure\", found {}",
+                envelope["status"]
+            )],
+            None if status == "success" => match &self.output {
+                Some(expected) => json_diff("$.result", expected, &envelope["result"], false),
+                None => Vec::new(),
+            },
+            None => vec![format!(
+                "$.status: expected \"success\", found {}, with the error {}",
+                envelope["status"], envelope["error"]
+            )],
+        }
+    }
+}
+
+/// Lists the differences between two JSON values, one per line, with the path they are at
+///
+/// # Arguments
+///
+/// * `path` - The path of the values, prefixing every difference
+/// * `expected` - The expected value
+/// * `actual` - The value to compare
+/// * `partial` - Whether the fields of objects missing from the expected value are ignored
+///
+pub fn json_diff(
+    path: &str,
+    expected: &serde_json::Value,
+    actual: &serde_json::Value,
+    partial: bool,
+) -> Vec<String> {
+    use serde_json::Value::{Array, Object};
+
+    match (expected, actual) {
+        (Object(expected), Object(actual)) => {
+            let mut diff = Vec::new();
+
+            for (key, value) in expected.iter() {
+                let field = format!("{}.{}", path, key);
+
+                match actual.get(key) {
+                    Some(actual) => diff.extend(json_diff(&field, value, actual, partial)),
+                    None => diff.push(format!("{}: expected {}, found nothing", field, value)),
+                }
+            }
+
+            if !partial {
+                for (key, value) in actual.iter() {
+                    if !expected.contains_key(key) {
+                        diff.push(format!(
+                            "{}.{}: expected nothing, found {}",
+                            path, key, value
+                        ));
+                    }
+                }
+            }
+
+            diff
+        }
+        (Array(expected), Array(actual)) if expected.len() == actual.len() => expected
+            .iter()
+            .zip(actual.iter())
+            .enumerate()
+            .flat_map(|(index, (expected, actual))| {
+                json_diff(&format!("{}[{}]", path, index), expected, actual, partial)
+            })
+            .collect(),
+        (expected, actual) if expected != actual => {
+            vec![format!("{}: expected {}, found {}", path, expected, actual)]
+        }
+        _ => Vec::new(),
+    }
+}
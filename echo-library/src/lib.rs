@@ -2,6 +2,7 @@
 use allocative::Allocative;
 use anyhow::Error;
 use convert_case::{Case, Casing};
+use indexmap::{IndexMap, IndexSet};
 use serde_derive::{Deserialize, Serialize};
 use starlark::environment::LibraryExtension::*;
 use starlark::environment::{GlobalsBuilder, Module};
@@ -19,6 +20,7 @@ use std::process::Command;
 use std::result::Result::Ok;
 
 mod common;
+#[cfg(test)]
 mod tests;
 mod types;
 
@@ -1,214 +1,2426 @@
-#[cfg(test)]
-mod tests {
-    use super::super::*;
-
-    #[test]
-    fn add_workflow_test_pass() {
-        let composer = Composer::default();
-
-        let workflow1 = Workflow {
-            name: "test-workflow".to_string(),
-            version: "0.0.1".to_string(),
-            tasks: HashMap::default(),
-        };
+mod workflow_boilerplate;
 
-        composer
-            .add_workflow(
-                "test-workflow".to_string(),
-                "0.0.1".to_string(),
-                HashMap::default(),
-            )
-            .unwrap();
+use super::*;
+use composer_primitives::types::{ManifestFile, SourceFiles};
+use quote::quote;
+
+fn compile_config(config: &str) -> Result<Composer, Error> {
+    compile_config_with(Composer::default(), config)
+}
+
+fn compile_config_with(composer: Composer, config: &str) -> Result<Composer, Error> {
+    let package = tempfile::tempdir()?;
+    fs::write(package.path().join("main.echo"), config)?;
 
-        let composer_workflow = &composer.workflows.borrow()[0];
+    let files = SourceFiles::new(Some(package.path().to_path_buf()))?;
+    composer.compile("main.echo", &files, &mut HashMap::new())?;
 
-        assert_eq!(composer_workflow, &workflow1);
+    Ok(composer)
+}
+
+fn single_diagnostic(error: &Error) -> &Diagnostic {
+    let diagnostics = error.downcast_ref::<Diagnostics>().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+
+    &diagnostics.0[0]
+}
+
+/// Task setting its `id` from the `ids` of each of the tasks it depends on
+fn task(action_name: &str, depend_on: &[&str]) -> Task {
+    Task {
+        action_name: action_name.to_string(),
+        depend_on: depend_on
+            .iter()
+            .map(|task_name| Depend {
+                task_name: task_name.to_string(),
+                cur_field: "id".to_string(),
+                prev_field: "ids".to_string(),
+            })
+            .collect(),
+        ..Default::default()
     }
+}
 
-    #[test]
-    fn get_dependencies_test() {
-        let composer = Composer::default();
+/// Tasks of a workflow, keyed by their action name
+fn tasks(tasks: Vec<Task>) -> IndexMap<String, Task> {
+    tasks
+        .into_iter()
+        .map(|task| (task.action_name.clone(), task))
+        .collect()
+}
 
-        let mut dependencies: Vec<Depend> = Vec::new();
-        dependencies.push(Depend {
-            task_name: "dependent_task".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
+fn workflow(tasks: Vec<Task>) -> Workflow {
+    Workflow {
+        name: "test-workflow".to_string(),
+        version: "0.0.1".to_string(),
+        tasks: self::tasks(tasks),
+    }
+}
 
-        let task = Task {
-            action_name: "get_salaries".to_string(),
-            depend_on: dependencies,
-            ..Default::default()
-        };
+/// Checks whether the generated code contains the tokens, regardless of its layout. The
+/// code is parsed back from its source, which joins adjacent punctuations unlike
+/// `quote!`, so both sides are compared with their punctuations apart
+fn contains_tokens(code: &str, tokens: proc_macro2::TokenStream) -> bool {
+    fn apart(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                proc_macro2::TokenTree::Group(group) => {
+                    proc_macro2::Group::new(group.delimiter(), apart(group.stream())).into()
+                }
+                proc_macro2::TokenTree::Punct(punct) => {
+                    proc_macro2::Punct::new(punct.as_char(), proc_macro2::Spacing::Alone).into()
+                }
+                token => token,
+            })
+            .collect()
+    }
 
-        let mut tasks = HashMap::<String, Task>::new();
-        tasks.insert("get_salaries".to_string(), task);
+    let code: proc_macro2::TokenStream = code.parse().unwrap();
 
-        composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+    apart(code).to_string().contains(&apart(tokens).to_string())
+}
+
+#[derive(Debug)]
+struct CustomKind;
+
+impl TaskKind for CustomKind {
+    fn name(&self) -> &str {
+        "custom_runtime"
+    }
+
+    fn derive(&self) -> &str {
+        "CustomRuntime"
+    }
+
+    fn imports(&self) -> Vec<String> {
+        vec!["use custom_runtime_macro::CustomRuntime;".to_string()]
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["custom_runtime_macro = \"0.1.0\"".to_string()]
+    }
+
+    fn attributes(&self) -> Vec<AttributeSchema> {
+        vec![AttributeSchema::required("endpoint")]
+    }
+}
+
+const CUSTOM_KIND_CONFIG: &str = r#"
+greet = task(
+    kind = "custom_runtime",
+    action_name = "greet",
+    input_arguments = [argument(name = "name", input_type = String)],
+    attributes = {"endpoint": "http://127.0.0.1:8080"},
+)
+
+workflows(name = "custom", version = "0.0.1", tasks = [greet])
+"#;
+
+#[test]
+fn add_workflow_test_pass() {
+    let composer = Composer::default();
+
+    composer
+        .add_workflow(
+            "test-workflow".to_string(),
+            "0.0.1".to_string(),
+            IndexMap::default(),
+        )
+        .unwrap();
+
+    let composer_workflow = &composer.workflows.borrow()[0];
+
+    assert_eq!(composer_workflow, &workflow(Vec::new()));
+}
+
+#[test]
+fn get_dependencies_test() {
+    let composer = Composer::default();
+
+    composer
+        .add_workflow(
+            "test-workflow".to_string(),
+            "0.0.1".to_string(),
+            tasks(vec![task("get_salaries", &["dependent_task"])]),
+        )
+        .unwrap();
+
+    assert_eq!(
+        composer.workflows.borrow()[0]
+            .get_dependencies("get_salaries")
+            .unwrap(),
+        vec!["dependent_task"]
+    );
+}
+
+#[test]
+fn get_flow_test() {
+    let composer = Composer::default();
+
+    let tasks = tasks(vec![
+        task("task0", &[]),
+        task("task1", &["task0", "task4"]),
+        task("task2", &["task0"]),
+        task("task3", &["task1", "task2"]),
+        task("task4", &[]),
+        task("task5", &["task2"]),
+    ]);
+
+    composer
+        .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+        .unwrap();
+
+    let flow = composer.workflows.borrow()[0].get_flow().unwrap();
+
+    assert_eq!(
+        flow,
+        vec!["task0", "task4", "task1", "task2", "task3", "task5"]
+    );
+}
+
+#[test]
+fn get_flow_cycle_test() {
+    let workflow = workflow(
+        [("task0", "task2"), ("task1", "task0"), ("task2", "task1")]
+            .into_iter()
+            .map(|(task_name, depend_task)| Task {
+                location: Some(Location {
+                    file: "main.echo".to_string(),
+                    line: 4,
+                    column: 9,
+                    ..Default::default()
+                }),
+                ..task(task_name, &[depend_task])
+            })
+            .collect(),
+    );
+
+    let error = workflow.get_flow().unwrap_err().to_string();
+
+    assert!(error.starts_with("Cycle detected in workflow 'test-workflow': "));
+    assert!(
+        error.contains("task0 -> task1 -> task2 -> task0")
+            || error.contains("task1 -> task2 -> task0 -> task1")
+            || error.contains("task2 -> task0 -> task1 -> task2")
+    );
+    assert!(error.contains("--> main.echo:4:9"));
+}
+
+#[test]
+fn get_flow_self_dependency_test() {
+    let workflow = workflow(vec![task("task0", &["task0"])]);
+
+    assert_eq!(
+        workflow.get_flow().unwrap_err().to_string(),
+        "Cycle detected in workflow 'test-workflow': task0 -> task0"
+    );
+}
+
+#[test]
+fn get_flow_unknown_dependency_test() {
+    let workflow = workflow(vec![task("task0", &["missing_task"])]);
+
+    assert_eq!(
+        workflow.get_flow().unwrap_err().to_string(),
+        "Task 'task0' in workflow 'test-workflow' depends on unknown task 'missing_task'"
+    );
+    assert_eq!(workflow.get_dependencies("missing_task"), None);
+}
+
+#[test]
+fn get_attributes_test() {
+    let composer = Composer::default();
+
+    let mut attributes: IndexMap<String, String> = IndexMap::new();
+    attributes.insert("namespace".to_string(), "value1".to_string());
+    attributes.insert("auth_key".to_string(), "value2".to_string());
+
+    composer
+        .add_workflow(
+            "test-workflow".to_string(),
+            "0.0.1".to_string(),
+            tasks(vec![Task {
+                attributes,
+                ..task("test-task", &[])
+            }]),
+        )
+        .unwrap();
+
+    let composer_task = &composer.workflows.borrow()[0].tasks;
+
+    let attributes =
+        parse_module::get_attributes(&composer_task.get("test-task").unwrap().attributes);
+
+    println!("{:#?}", attributes);
+
+    assert_eq!(
+        attributes.unwrap().to_string(),
+        quote!([Namespace: "value1", AuthKey: "value2"]).to_string()
+    );
+}
+
+#[test]
+fn generate_types_rs_file_code_is_deterministic_test() {
+    let config = r#"
+attributes = {
+    "namespace": "guest",
+    "api_host": "http://127.0.0.1:1234",
+    "insecure": "true",
+    "auth_key": "key",
+}
+
+zeta = task(
+    kind = "openwhisk",
+    action_name = "zeta",
+    input_arguments = [argument(name = "id", input_type = Int)],
+    attributes = attributes,
+)
+
+alpha = task(
+    kind = "openwhisk",
+    action_name = "alpha",
+    input_arguments = [argument(name = "ids", input_type = List(Int))],
+    attributes = attributes,
+    depend_on = [depend(task_name = "zeta", cur_field = "ids", prev_field = "ids")],
+)
+
+mid = task(
+    kind = "openwhisk",
+    action_name = "mid",
+    input_arguments = [argument(name = "person", input_type = EchoStruct(name = "Person", fields = {"name": String, "age": Int}))],
+    attributes = attributes,
+    depend_on = [depend(task_name = "alpha", cur_field = "person", prev_field = "person")],
+)
+
+workflows(name = "ordering", version = "0.0.1", tasks = [zeta, alpha, mid])
+"#;
+
+    let generate = || {
+        let composer = compile_config(config).unwrap();
+        let workflow = composer.workflows.borrow()[0].clone();
+        let custom_types = composer.custom_types.borrow();
+        let task_kinds = composer.task_kinds.borrow();
+
+        generate_types_rs_file_code(&workflow, &custom_types, &task_kinds).unwrap()
+    };
+
+    let first = generate();
+
+    for _ in 0..10 {
+        assert_eq!(first, generate());
+    }
+
+    let main_structs: Vec<String> = syn::parse_file(&first)
+        .unwrap()
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            syn::Item::Macro(item) if item.mac.path.is_ident("make_main_struct") => item
+                .mac
+                .tokens
+                .into_iter()
+                .next()
+                .map(|token| token.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(main_structs, ["Zeta", "Alpha", "Mid"]);
+    assert!(contains_tokens(
+        &first,
+        quote!([Namespace: "guest", ApiHost: "http://127.0.0.1:1234", Insecure: "true", AuthKey: "key"])
+    ));
+    assert!(contains_tokens(&first, quote!([name: String, age: i32])));
+    assert!(contains_tokens(
+        &first,
+        quote!(impl_execute_trait!(Zeta, Alpha, Mid);)
+    ));
+}
+
+#[test]
+fn get_task_kind_test_pass() {
+    let task_kinds = TaskKinds::default();
+
+    let kind_name = get_task_kind(&task_kinds, "polkadot").unwrap();
+    assert_eq!(&kind_name, "Polkadot");
+
+    let kind_name = get_task_kind(&task_kinds, "openwhisk").unwrap();
+    assert_eq!(&kind_name, "OpenWhisk");
+
+    let kind_name = get_task_kind(&task_kinds, "hello_world").unwrap();
+    assert_eq!(&kind_name, "HelloWorldDerive");
+}
+
+#[test]
+#[should_panic]
+fn get_task_kind_test_fail() {
+    let kind_name = get_task_kind(&TaskKinds::default(), "polkadot").unwrap();
+    assert_eq!(&kind_name, "polkadot");
+}
+
+#[test]
+fn get_task_kind_unknown_kind_test() {
+    assert_eq!(
+        get_task_kind(&TaskKinds::default(), "custom_runtime"),
+        Err(ErrorKind::NotFound)
+    );
+}
+
+#[test]
+fn add_task_kind_test() {
+    let mut composer = Composer::default();
+    composer.add_task_kind(CustomKind);
+
+    let composer = compile_config_with(composer, CUSTOM_KIND_CONFIG).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+    let task_kinds = composer.task_kinds.borrow();
+
+    let types_rs =
+        generate_types_rs_file_code(workflow, &composer.custom_types.borrow(), &task_kinds)
             .unwrap();
 
-        assert_eq!(
-            composer.workflows.borrow()[0]
-                .get_dependencies("get_salaries")
-                .unwrap(),
-            vec!["dependent_task"]
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(
+            use super::*;
+            use custom_runtime_macro::CustomRuntime;
+        )
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!([Debug, Clone, Default, Serialize, Deserialize, CustomRuntime])
+    ));
+    assert_eq!(
+        generate_cargo_toml_dependencies(workflow, &task_kinds),
+        "custom_runtime_macro = \"0.1.0\"\n"
+    );
+}
+
+#[test]
+fn unknown_task_kind_test() {
+    let error = compile_config(CUSTOM_KIND_CONFIG)
+        .err()
+        .unwrap()
+        .to_string();
+
+    assert!(error.contains(
+            "Unsupported task kind: custom_runtime, expected one of: openwhisk, polkadot, hello_world, mock"
+        ));
+}
+
+#[test]
+fn missing_required_attribute_test() {
+    let mut composer = Composer::default();
+    composer.add_task_kind(CustomKind);
+
+    let config = CUSTOM_KIND_CONFIG.replace("endpoint", "endpiont");
+    let error = compile_config_with(composer, &config).err().unwrap();
+    let diagnostics = error.downcast_ref::<Diagnostics>().unwrap();
+
+    // The misspelled attribute is reported first, along with the name it is close to
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.hint.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "Unknown attribute `endpiont` for the task of kind: custom_runtime",
+                Some("did you mean `endpoint`?")
+            ),
+            (
+                "Missing attribute `endpoint` for the task of kind: custom_runtime",
+                Some("add `endpoint` to the attributes of the task")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn generate_cargo_toml_dependencies_test() {
+    let workflow = workflow(
+        [
+            ("task0", "Polkadot"),
+            ("task1", "openwhisk"),
+            ("task2", "polkadot"),
+        ]
+        .into_iter()
+        .map(|(task_name, kind)| Task {
+            kind: kind.to_string(),
+            ..task(task_name, &[])
+        })
+        .collect(),
+    );
+
+    let task_kinds = TaskKinds::default();
+    let dependencies = generate_cargo_toml_dependencies(&workflow, &task_kinds);
+
+    assert!(dependencies.starts_with("substrate_macro = \"0.1.3\"\n"));
+    assert!(dependencies.ends_with("openwhisk_macro = \"0.1.6\"\n"));
+    assert_eq!(dependencies.matches("substrate_macro").count(), 1);
+    assert_eq!(
+        get_kind_imports(&workflow, &task_kinds)
+            .unwrap()
+            .to_string(),
+        quote! {
+            use substrate_macro::Polkadot;
+            use sp_core::H256;
+            use openwhisk_macro::OpenWhisk;
+        }
+        .to_string()
+    );
+    assert!(get_kind_library_code(&workflow, &task_kinds).contains("pub struct StakingLedger"));
+}
+
+const CUSTOM_KIND_MANIFEST: &str = r#"
+[kinds.custom_runtime]
+derive = "CustomRuntime"
+imports = ["use custom_runtime_macro::CustomRuntime;"]
+
+[kinds.custom_runtime.dependencies]
+custom_runtime_macro = "0.1.0"
+custom_runtime = { git = "https://example.com/custom_runtime.git", rev = "abc123" }
+
+[kinds.custom_runtime.attributes]
+endpoint = { required = true }
+token = {}
+"#;
+
+#[test]
+fn load_task_kinds_from_manifest_test() {
+    let package = tempfile::tempdir().unwrap();
+    fs::write(package.path().join("main.echo"), CUSTOM_KIND_CONFIG).unwrap();
+    fs::write(package.path().join("composer.toml"), CUSTOM_KIND_MANIFEST).unwrap();
+
+    let files = SourceFiles::new(Some(package.path().to_path_buf())).unwrap();
+    let composer = Composer::default();
+    composer.load_task_kinds(files.manifests()).unwrap();
+    composer
+        .compile("main.echo", &files, &mut HashMap::new())
+        .unwrap();
+
+    let workflows = composer.workflows.borrow();
+    let task_kinds = composer.task_kinds.borrow();
+    let kind = task_kinds.get("custom_runtime").unwrap();
+
+    assert_eq!(
+        kind.attributes(),
+        vec![
+            AttributeSchema::required("endpoint"),
+            AttributeSchema::optional("token")
+        ]
+    );
+    assert_eq!(
+            generate_cargo_toml_dependencies(&workflows[0], &task_kinds),
+            "custom_runtime_macro = \"0.1.0\"\ncustom_runtime = { git = \"https://example.com/custom_runtime.git\", rev = \"abc123\" }\n"
         );
-    }
+    assert_eq!(
+        get_kind_imports(&workflows[0], &task_kinds)
+            .unwrap()
+            .to_string(),
+        quote!(
+            use custom_runtime_macro::CustomRuntime;
+        )
+        .to_string()
+    );
+}
 
-    #[test]
-    fn get_flow_test() {
-        let composer = Composer::default();
+#[test]
+fn glob_kind_import_test() {
+    let package = tempfile::tempdir().unwrap();
+    fs::write(package.path().join("main.echo"), CUSTOM_KIND_CONFIG).unwrap();
+    fs::write(
+        package.path().join("composer.toml"),
+        CUSTOM_KIND_MANIFEST.replace(
+            "use custom_runtime_macro::CustomRuntime;",
+            "use custom_runtime_macro::{CustomRuntime, client::*};",
+        ),
+    )
+    .unwrap();
 
-        let task0 = Task {
-            action_name: "task0".to_string(),
-            ..Default::default()
-        };
-        let mut task1 = Task {
-            action_name: "task1".to_string(),
-            ..Default::default()
-        };
+    let files = SourceFiles::new(Some(package.path().to_path_buf())).unwrap();
+    let composer = Composer::default();
+    composer.load_task_kinds(files.manifests()).unwrap();
+    composer
+        .compile("main.echo", &files, &mut HashMap::new())
+        .unwrap();
 
-        let mut dependencies: Vec<Depend> = Vec::new();
-        dependencies.push(Depend {
-            task_name: "task0".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        dependencies.push(Depend {
-            task_name: "task4".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        task1.depend_on = dependencies;
-
-        let mut task2 = Task {
-            action_name: "task2".to_string(),
-            ..Default::default()
-        };
+    assert_eq!(
+        get_kind_imports(
+            &composer.workflows.borrow()[0],
+            &composer.task_kinds.borrow()
+        )
+        .unwrap_err()
+        .to_string(),
+        "Invalid import for the kind custom_runtime: `use custom_runtime_macro::{CustomRuntime, \
+             client::*};` is a glob import, import the items it uses by name"
+    );
+}
 
-        let mut dependencies: Vec<Depend> = Vec::new();
-        dependencies.push(Depend {
-            task_name: "task0".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        task2.depend_on = dependencies;
+#[test]
+fn load_task_kinds_from_kinds_directory_test() {
+    let package = tempfile::tempdir().unwrap();
+    fs::create_dir(package.path().join("kinds")).unwrap();
+    fs::write(
+        package.path().join("kinds").join("custom_runtime.toml"),
+        "derive = \"CustomRuntime\"\nattributes = { endpoint = { required = true } }\n",
+    )
+    .unwrap();
 
-        let mut task3 = Task {
-            action_name: "task3".to_string(),
-            ..Default::default()
-        };
+    let files = SourceFiles::new(Some(package.path().to_path_buf())).unwrap();
+    let mut task_kinds = TaskKinds::default();
+    task_kinds.load(files.manifests()).unwrap();
 
-        let mut dependencies: Vec<Depend> = Vec::new();
-        dependencies.push(Depend {
-            task_name: "task1".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        dependencies.push(Depend {
-            task_name: "task2".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        task3.depend_on = dependencies;
-
-        let task4 = Task {
-            action_name: "task4".to_string(),
-            ..Default::default()
-        };
-        let mut task5 = Task {
-            action_name: "task5".to_string(),
+    let kind = task_kinds.get("Custom_Runtime").unwrap();
+    assert_eq!(kind.derive(), "CustomRuntime");
+    assert_eq!(
+        kind.attributes(),
+        vec![AttributeSchema::required("endpoint")]
+    );
+}
+
+#[test]
+fn load_task_kinds_from_package_named_kinds_test() {
+    let parent = tempfile::tempdir().unwrap();
+    let package = parent.path().join("kinds");
+    fs::create_dir(&package).unwrap();
+    fs::write(package.join("composer.toml"), CUSTOM_KIND_MANIFEST).unwrap();
+
+    let files = SourceFiles::new(Some(package)).unwrap();
+    let mut task_kinds = TaskKinds::default();
+    task_kinds.load(files.manifests()).unwrap();
+
+    assert_eq!(
+        task_kinds.get("custom_runtime").unwrap().derive(),
+        "CustomRuntime"
+    );
+    assert!(task_kinds.get("composer").is_none());
+}
+
+#[test]
+fn load_task_kinds_invalid_manifest_test() {
+    let package = tempfile::tempdir().unwrap();
+    let manifest = package.path().join("composer.toml");
+    fs::write(&manifest, "[kinds.custom_runtime]\nimports = []\n").unwrap();
+
+    let error = TaskKinds::default()
+        .load(&[ManifestFile::Package(manifest.clone())])
+        .err()
+        .unwrap()
+        .to_string();
+
+    assert!(error.starts_with(&format!("Invalid manifest {}", manifest.display())));
+    assert!(error.contains("missing field `derive`"));
+}
+
+const POLKADOT_CONFIG: &str = r#"
+payout = task(
+    kind = "polkadot",
+    action_name = "payout",
+    input_arguments = [argument(name = "era", input_type = String)],
+    attributes = {"chain": "westend", "operation": "stakingpayout"},
+)
+
+workflows(name = "payout", version = "0.0.1", tasks = [payout])
+"#;
+
+#[test]
+fn valid_attributes_test() {
+    assert!(compile_config(POLKADOT_CONFIG).is_ok());
+}
+
+#[test]
+fn unknown_attribute_test() {
+    let config = POLKADOT_CONFIG.replace(
+        r#""operation": "stakingpayout""#,
+        r#""operation": "stakingpayout", "chian": "westend""#,
+    );
+    let error = compile_config(&config).err().unwrap();
+    let diagnostic = single_diagnostic(&error);
+
+    assert_eq!(diagnostic.code, DiagnosticCode::InvalidAttribute);
+    assert_eq!(
+        diagnostic.message,
+        "Unknown attribute `chian` for the task of kind: polkadot"
+    );
+    assert_eq!(diagnostic.hint.as_deref(), Some("did you mean `chain`?"));
+    assert_eq!(diagnostic.task.as_deref(), Some("payout"));
+    assert_eq!(diagnostic.location.as_ref().unwrap().line, 2);
+}
+
+#[test]
+fn every_invalid_attribute_test() {
+    let config = POLKADOT_CONFIG.replace(
+        r#"{"chain": "westend", "operation": "stakingpayout"}"#,
+        r#"{"chain": "rococo", "operation": "stakingpayout", "chian": "westend"}"#,
+    );
+
+    assert_eq!(
+            type_check(&config),
+            vec![
+                (
+                    DiagnosticCode::InvalidAttribute,
+                    "Invalid value `rococo` for attribute `chain` of the task of kind: polkadot, expected one of: westend, polkadot, kusama".to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidAttribute,
+                    "Unknown attribute `chian` for the task of kind: polkadot".to_string()
+                ),
+            ]
+        );
+}
+
+#[test]
+fn invalid_attribute_value_test() {
+    let config = POLKADOT_CONFIG.replace("westend", "rococo");
+    let error = compile_config(&config).err().unwrap().to_string();
+
+    assert!(error.contains(
+            "Invalid value `rococo` for attribute `chain` of the task of kind: polkadot, expected one of: westend, polkadot, kusama"
+        ));
+}
+
+#[test]
+fn attribute_format_test() {
+    assert!(AttributeFormat::Url.check("https://127.0.0.1:1234").is_ok());
+    assert!(AttributeFormat::Url.check("127.0.0.1:1234").is_err());
+    assert!(AttributeFormat::Url.check("http://").is_err());
+    assert!(AttributeFormat::Bool.check("false").is_ok());
+    assert_eq!(
+        AttributeFormat::Bool.check("yes"),
+        Err("expected `true` or `false`".to_string())
+    );
+    assert!(AttributeFormat::Any.check("").is_ok());
+}
+
+#[test]
+fn manifest_attribute_format_test() {
+    let package = tempfile::tempdir().unwrap();
+    let manifest = package.path().join("composer.toml");
+    fs::write(
+            &manifest,
+            "[kinds.custom_runtime]\nderive = \"CustomRuntime\"\n\n[kinds.custom_runtime.attributes]\nendpoint = { required = true, format = \"url\" }\nregion = { values = [\"eu\", \"us\"] }\n",
+        )
+        .unwrap();
+
+    let mut task_kinds = TaskKinds::default();
+    task_kinds
+        .load(&[ManifestFile::Package(manifest.clone())])
+        .unwrap();
+    let kind = task_kinds.get("custom_runtime").unwrap();
+
+    let mut attributes = IndexMap::new();
+    attributes.insert("endpoint".to_string(), "http://127.0.0.1:8080".to_string());
+    attributes.insert("region".to_string(), "eu".to_string());
+    assert!(kind.validate_attributes(&attributes).is_empty());
+
+    attributes.insert("region".to_string(), "asia".to_string());
+    assert!(kind.validate_attributes(&attributes)[0]
+        .to_string()
+        .ends_with("expected one of: eu, us"));
+}
+
+#[test]
+fn syntax_error_diagnostic_test() {
+    let error = compile_config("greet = task(\n").err().unwrap();
+    let diagnostic = single_diagnostic(&error);
+
+    assert_eq!(diagnostic.code, DiagnosticCode::Syntax);
+    assert!(diagnostic.message.starts_with("Parse error"));
+    assert!(diagnostic
+        .location
+        .as_ref()
+        .unwrap()
+        .file
+        .ends_with("main.echo"));
+}
+
+#[test]
+fn evaluation_error_diagnostic_test() {
+    let error = compile_config("\ngreet = undefined_task\n").err().unwrap();
+    let diagnostic = single_diagnostic(&error);
+    let location = diagnostic.location.as_ref().unwrap();
+
+    assert_eq!(diagnostic.code, DiagnosticCode::Evaluation);
+    assert_eq!(diagnostic.message, "Variable `undefined_task` not found");
+    assert_eq!((location.line, location.column), (2, 9));
+    assert_eq!(location.end - location.begin, "undefined_task".len());
+}
+
+#[test]
+fn duplicate_task_diagnostic_test() {
+    let config = CUSTOM_KIND_CONFIG.replace("tasks = [greet]", "tasks = [greet, greet]");
+    let mut composer = Composer::default();
+    composer.add_task_kind(CustomKind);
+
+    let error = compile_config_with(composer, &config).err().unwrap();
+    let diagnostic = single_diagnostic(&error);
+
+    assert_eq!(diagnostic.code, DiagnosticCode::DuplicateTask);
+    assert_eq!(diagnostic.workflow.as_deref(), Some("custom"));
+    assert_eq!(diagnostic.task.as_deref(), Some("greet"));
+    assert_eq!(diagnostic.location.as_ref().unwrap().line, 2);
+    assert_eq!(
+            diagnostic.to_string(),
+            format!(
+                "Duplicate tasks, Task names must be unique\n  --> {} (task 'greet')\n  = hint: rename one of the tasks with the action name 'greet'",
+                diagnostic.location.as_ref().unwrap()
+            )
+        );
+}
+
+#[test]
+fn compile_reports_every_problem_test() {
+    let config = r#"
+first = task(
+    kind = "openwhisk",
+    action_name = "first",
+    input_arguments = [argument(name = "count", input_type = Int, default_value = "ten")],
+    attributes = {"api_host": "127.0.0.1", "auth_key": "key", "namespace": "guest"},
+)
+
+second = task(
+    kind = "unknown",
+    action_name = "second",
+    input_arguments = [argument(name = "total", input_type = Int)],
+    depend_on = [depend(task_name = "first", cur_field = "totl", prev_field = "count")],
+)
+
+third = task(
+    kind = "hello_world",
+    action_name = "third",
+    input_arguments = [argument(name = "value", input_type = Int)],
+    depend_on = [depend(task_name = "missing", cur_field = "value", prev_field = "value")],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [first, second, third, third])
+"#;
+
+    let error = compile_config(config).err().unwrap();
+    let diagnostics = error.downcast_ref::<Diagnostics>().unwrap();
+
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect::<Vec<DiagnosticCode>>(),
+        vec![
+            DiagnosticCode::TypeMismatch,
+            DiagnosticCode::InvalidAttribute,
+            DiagnosticCode::UnknownKind,
+            DiagnosticCode::UnknownField,
+            DiagnosticCode::DuplicateTask,
+            DiagnosticCode::UnknownDependency,
+        ]
+    );
+    assert!(error.to_string().ends_with("\n\n6 errors found"));
+}
+
+#[test]
+fn missing_custom_type_test() {
+    let composer = Composer::default();
+    let tasks = tasks(vec![Task {
+        input_arguments: vec![Input {
+            name: "cars".to_string(),
+            input_type: RustType::List(Box::new(RustType::Struct("Car".to_string()))),
             ..Default::default()
+        }],
+        ..task("task0", &[])
+    }]);
+
+    composer
+        .add_workflow("workflow".to_string(), "0.0.1".to_string(), tasks)
+        .unwrap();
+
+    let diagnostics = composer.validate();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, DiagnosticCode::MissingCustomType);
+    assert_eq!(
+        diagnostics[0].message,
+        "Missing custom type `Car` of the argument `cars`"
+    );
+}
+
+const TYPED_OUTPUT_CONFIG: &str = r#"
+employee_ids = task(
+    kind = "hello_world",
+    action_name = "employee_ids",
+    input_arguments = [argument(name = "role", input_type = String)],
+    output = {"ids": List(Int)},
+)
+
+getsalaries = task(
+    kind = "hello_world",
+    action_name = "getsalaries",
+    input_arguments = [argument(name = "id", input_type = Int)],
+    operation = Operation.map("salary"),
+    depend_on = [depend(task_name = "employee_ids", cur_field = "id", prev_field = "ids")],
+    output = {"salary": Int},
+)
+
+getaddress = task(
+    kind = "hello_world",
+    action_name = "getaddress",
+    input_arguments = [argument(name = "id", input_type = Int)],
+    operation = Operation.map("address"),
+    depend_on = [depend(task_name = "employee_ids", cur_field = "id", prev_field = "ids")],
+    output = {"address": String},
+)
+
+salary = task(
+    kind = "hello_world",
+    action_name = "salary",
+    input_arguments = [argument(name = "details", input_type = HashMap(Int, Tuple(Int, String)))],
+    operation = Operation.concat(),
+    depend_on = [
+        depend(task_name = "getsalaries", cur_field = "details", prev_field = "result"),
+        depend(task_name = "getaddress", cur_field = "details", prev_field = "result"),
+    ],
+)
+
+company = task(
+    kind = "hello_world",
+    action_name = "company",
+    input_arguments = [argument(name = "company_id", input_type = String)],
+    output = {"name": String},
+)
+
+report = task(
+    kind = "hello_world",
+    action_name = "report",
+    input_arguments = [
+        argument(name = "company_name", input_type = String),
+        argument(name = "addresses", input_type = HashMap(Int, String)),
+    ],
+    operation = Operation.combine(),
+    depend_on = [
+        depend(task_name = "company", cur_field = "company_name", prev_field = "name"),
+        depend(task_name = "getaddress", cur_field = "addresses", prev_field = "result"),
+    ],
+)
+
+workflows(
+    name = "typed",
+    version = "0.0.1",
+    tasks = [employee_ids, getsalaries, getaddress, salary, company, report],
+)
+"#;
+
+fn type_check(config: &str) -> Vec<(DiagnosticCode, String)> {
+    match compile_config(config) {
+        Ok(_) => Vec::new(),
+        Err(error) => error
+            .downcast_ref::<Diagnostics>()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.message.clone()))
+            .collect(),
+    }
+}
+
+#[test]
+fn typed_outputs_test() {
+    assert_eq!(type_check(TYPED_OUTPUT_CONFIG), vec![]);
+
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    assert_eq!(
+        composer.workflows.borrow()[0].tasks["employee_ids"].output["ids"],
+        RustType::List(Box::new(RustType::Int))
+    );
+}
+
+#[test]
+fn typed_output_code_generation_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+
+    let types_rs = generate_types_rs_file_code(
+        workflow,
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(make_input_struct!(EmployeeIdsOutput, [pub ids: Vec<i32>], [Debug, Clone, Default, Serialize, Deserialize]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(make_input_struct!(CompanyOutput, [pub name: String], [Debug, Clone, Default, Serialize, Deserialize]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(impl_map_setter!(Getsalaries, id: ids as EmployeeIdsOutput => "employee_ids", i32, "salary");)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(impl_concat_setter!(Salary, details, ["getsalaries", "getaddress"]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(impl_combine_setter!(Report, [[0] company_name: name as CompanyOutput => "company", (value) [1] addresses: "result" => "getaddress"]);)
+    ));
+}
+
+const KEYWORD_NAMES_CONFIG: &str = r#"
+Match = EchoStruct(name = "Match", fields = {"loop": Int})
+
+type = task(
+    kind = "hello_world",
+    action_name = "type",
+    input_arguments = [argument(name = "match", input_type = Match)],
+    output = {"ref": Int},
+)
+
+dyn = task(
+    kind = "hello_world",
+    action_name = "dyn",
+    input_arguments = [argument(name = "async", input_type = Int)],
+    depend_on = [depend(task_name = "type", cur_field = "async", prev_field = "ref")],
+)
+
+workflows(name = "keywords", version = "0.0.1", tasks = [type, dyn])
+"#;
+
+#[test]
+fn keyword_names_are_escaped_test() {
+    let composer = compile_config(KEYWORD_NAMES_CONFIG).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+
+    let types_rs = generate_types_rs_file_code(
+        workflow,
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(make_input_struct!(Match, [r#loop: i32], [Default, Clone, Debug, Deserialize, Serialize]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(make_input_struct!(TypeOutput, [pub r#ref: i32], [Debug, Clone, Default, Serialize, Deserialize]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(impl_setter!(Dyn, [r#async: r#ref as TypeOutput => "type"]);)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(let r#type = Type::new(input.r#match, "type".to_string());)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(let type_index = workflow.add_node(Box::new(r#type));)
+    ));
+}
+
+#[test]
+fn invalid_identifier_test() {
+    let config = r#"
+first = task(
+    kind = "hello_world",
+    action_name = "2fa",
+    input_arguments = [
+        argument(name = "my-arg", input_type = Int),
+        argument(name = "count", input_type = Int),
+        argument(name = "count", input_type = Int),
+    ],
+    output = {"self": Int},
+)
+
+second = task(
+    kind = "hello_world",
+    action_name = "input",
+    input_arguments = [],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [first, second])
+"#;
+
+    assert_eq!(
+            type_check(config),
+            vec![
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "Invalid argument name: `my-arg` is not a valid Rust identifier".to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "The task name '2fa' cannot be generated: `2Fa` is not a valid Rust identifier"
+                        .to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "Duplicate argument `count`, argument names must be unique".to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "Invalid output field: `self` is a Rust keyword which cannot be used as a name"
+                        .to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "The task name 'input' is generated as `Input`, which is reserved by the generated code"
+                        .to_string()
+                ),
+            ]
+        );
+}
+
+#[test]
+fn task_name_collision_test() {
+    let config = r#"
+Person = EchoStruct(name = "Person", fields = {"name": String})
+
+first = task(kind = "hello_world", action_name = "my-task", input_arguments = [])
+second = task(kind = "hello_world", action_name = "my_task", input_arguments = [])
+third = task(kind = "hello_world", action_name = "person", input_arguments = [])
+
+workflows(name = "workflow", version = "0.0.1", tasks = [first, second, third])
+"#;
+
+    let error = compile_config(config).err().unwrap();
+    let diagnostics = error.downcast_ref::<Diagnostics>().unwrap();
+
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|diagnostic| (
+                diagnostic.task.as_deref().unwrap(),
+                diagnostic.message.as_str()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "my_task",
+                "Tasks 'my-task' and 'my_task' are both generated as `MyTaskInput`"
+            ),
+            (
+                "person",
+                "Task 'person' is generated as `Person`, the name of an EchoStruct"
+            ),
+        ]
+    );
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.code
+        == DiagnosticCode::InvalidIdentifier
+        && diagnostic.workflow.as_deref() == Some("workflow")));
+}
+
+#[test]
+fn generated_scope_collision_test() {
+    let config = r#"
+Map = EchoStruct(name = "hash_map", fields = {"key": String})
+
+value = task(
+    kind = "hello_world",
+    action_name = "value",
+    input_arguments = [argument(name = "entries", input_type = Map)],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [value])
+"#;
+
+    assert_eq!(
+            type_check(config),
+            vec![
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "Task 'value' is generated as `Value`, which is already in scope of the generated code"
+                        .to_string()
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "EchoStruct `HashMap` is already in scope of the generated code".to_string()
+                ),
+            ]
+        );
+
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let scope = get_generated_scope(
+        &composer.workflows.borrow()[0],
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    for name in [
+        "Value",
+        "HashMap",
+        "WorkflowGraph",
+        "Output",
+        "Result",
+        "WskProperties",
+    ] {
+        assert!(scope.contains(name), "{} is not in scope", name);
+    }
+
+    // The names of the prelude are those of the edition of the generated crates
+    assert!(boilerplate::CARGO.contains("edition = \"2018\""));
+}
+
+#[test]
+fn echo_struct_collision_test() {
+    let config = r#"
+First = EchoStruct(name = "my_type", fields = {"x": Int})
+Second = EchoStruct(name = "MyType", fields = {"y": String})
+
+first = task(
+    kind = "hello_world",
+    action_name = "first",
+    input_arguments = [argument(name = "value", input_type = First)],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [first])
+"#;
+
+    let error = compile_config(config).err().unwrap();
+    let diagnostic = single_diagnostic(&error);
+
+    assert_eq!(diagnostic.code, DiagnosticCode::InvalidIdentifier);
+    assert_eq!(
+        diagnostic.message,
+        "EchoStruct 'MyType' is generated as `MyType`, the name of another EchoStruct"
+    );
+    assert_eq!(diagnostic.location.as_ref().unwrap().line, 3);
+}
+
+#[test]
+fn invalid_echo_struct_test() {
+    let config = r#"
+First = EchoStruct(name = "input", fields = {"x": Int})
+Second = EchoStruct(name = "second", fields = {"self": Int, "2x": String})
+
+first = task(
+    kind = "unknown_kind",
+    action_name = "first",
+    input_arguments = [
+        argument(name = "one", input_type = First),
+        argument(name = "two", input_type = Second),
+    ],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [first])
+"#;
+
+    let error = compile_config(config).err().unwrap();
+    let diagnostics = error.downcast_ref::<Diagnostics>().unwrap();
+
+    assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.code,
+                    diagnostic.message.as_str(),
+                    diagnostic.location.as_ref().unwrap().line
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "`Input` is reserved by the generated code",
+                    2
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "`self` is a Rust keyword which cannot be used as a name",
+                    3
+                ),
+                (
+                    DiagnosticCode::InvalidIdentifier,
+                    "`2x` is not a valid Rust identifier",
+                    3
+                ),
+                (
+                    DiagnosticCode::UnknownKind,
+                    "Unsupported task kind: unknown_kind, expected one of: openwhisk, polkadot, hello_world, mock",
+                    5
+                ),
+            ]
+        );
+}
+
+#[test]
+fn shared_argument_test() {
+    let config = r#"
+t1 = task(
+    kind = "hello_world",
+    action_name = "t1",
+    input_arguments = [argument(name = "id", input_type = String)],
+)
+
+t2 = task(
+    kind = "hello_world",
+    action_name = "t2",
+    input_arguments = [argument(name = "id", input_type = Int)],
+)
+
+workflows(name = "workflow", version = "0.0.1", tasks = [t1, t2])
+"#;
+
+    assert_eq!(
+        type_check(config),
+        vec![(
+            DiagnosticCode::InvalidIdentifier,
+            "Tasks 't1' and 't2' both take the argument `id`, as `String` and `i32`".to_string()
+        )]
+    );
+
+    let config = config.replace("input_type = Int", "input_type = String");
+    let default = r#"argument(name = "id", input_type = String, default_value = "x")"#;
+    let with_defaults = config.replace(r#"argument(name = "id", input_type = String)"#, default);
+
+    let composer = compile_config(&with_defaults).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+    let types_rs = generate_types_rs_file_code(
+        workflow,
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(make_input_struct!(Input, [#["id_fn"] id: String], [Debug, Clone, Default, Serialize, Deserialize]);)
+    ));
+    assert_eq!(types_rs.matches("pub fn id_fn").count(), 1);
+
+    let with_one_default =
+        config.replacen(r#"argument(name = "id", input_type = String)"#, default, 1);
+
+    assert_eq!(
+        type_check(&with_one_default),
+        vec![(
+            DiagnosticCode::InvalidIdentifier,
+            "Tasks 't1' and 't2' both take the argument `id`, with different default values"
+                .to_string()
+        )]
+    );
+}
+
+#[test]
+fn map_input_type_mismatch_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        r#"output = {"ids": List(Int)}"#,
+        r#"output = {"ids": List(String)}"#,
+    );
+
+    assert_eq!(
+            type_check(&config),
+            vec![
+                (
+                    DiagnosticCode::TypeMismatch,
+                    "Field `ids` of task 'employee_ids' is `Vec<String>`, but the input `id` expects `Vec<i32>`".to_string()
+                ),
+                (
+                    DiagnosticCode::TypeMismatch,
+                    "Field `ids` of task 'employee_ids' is `Vec<String>`, but the input `id` expects `Vec<i32>`".to_string()
+                ),
+            ]
+        );
+}
+
+#[test]
+fn map_task_with_several_arguments_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        r#"    input_arguments = [argument(name = "id", input_type = Int)],
+    operation = Operation.map("salary"),"#,
+        r#"    input_arguments = [
+        argument(name = "currency", input_type = String),
+        argument(name = "id", input_type = Int),
+    ],
+    operation = Operation.map("salary"),"#,
+    );
+
+    let composer = compile_config(&config).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+
+    let types_rs = generate_types_rs_file_code(
+        workflow,
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(impl_map_setter!(
+                Getsalaries, id: ids as EmployeeIdsOutput => "employee_ids", i32, "salary"
+            );)
+    ));
+}
+
+#[test]
+fn unknown_output_field_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        r#"cur_field = "company_name", prev_field = "name""#,
+        r#"cur_field = "company_name", prev_field = "title""#,
+    );
+
+    assert_eq!(
+        type_check(&config),
+        vec![(
+            DiagnosticCode::UnknownField,
+            "Unknown field `title` in the output of task 'company'".to_string()
+        )]
+    );
+}
+
+#[test]
+fn concat_type_mismatch_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        "HashMap(Int, Tuple(Int, String))",
+        "HashMap(Int, Tuple(String, String))",
+    );
+
+    assert_eq!(
+            type_check(&config),
+            vec![(
+                DiagnosticCode::TypeMismatch,
+                "Concat task 'salary' produces `HashMap<i32,(i32,String)>`, but the input `details` expects `HashMap<i32,(String,String)>`".to_string()
+            )]
+        );
+}
+
+#[test]
+fn combine_type_mismatch_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        r#"argument(name = "addresses", input_type = HashMap(Int, String))"#,
+        r#"argument(name = "addresses", input_type = HashMap(String, String))"#,
+    );
+
+    assert_eq!(
+            type_check(&config),
+            vec![(
+                DiagnosticCode::TypeMismatch,
+                "Field `result` of task 'getaddress' is `HashMap<i32,String>`, but the input `addresses` expects `HashMap<String,String>`".to_string()
+            )]
+        );
+}
+
+#[test]
+fn concat_dependency_count_test() {
+    let config = TYPED_OUTPUT_CONFIG.replace(
+        r#"        depend(task_name = "getaddress", cur_field = "details", prev_field = "result"),
+    ],
+)
+
+company"#,
+        r#"    ],
+)
+
+company"#,
+    );
+
+    assert_eq!(
+        type_check(&config),
+        vec![(
+            DiagnosticCode::InvalidOperation,
+            "Concat task 'salary' must depend on exactly two tasks, found 1".to_string()
+        )]
+    );
+}
+
+/// Signatures of the functions crossing the ABI of the generated workflows, in the text
+/// format of wasm and keyed by their import or export name, read from the boilerplate
+/// and the library of the mock kind
+#[cfg(feature = "runtime")]
+fn workflow_abi() -> std::collections::BTreeMap<String, String> {
+    fn signature(signature: &syn::Signature) -> String {
+        let wasm_type = |ty: &syn::Type| match quote!(#ty).to_string().as_str() {
+            "i32" | "u32" => "i32",
+            // Pointers are 32 bits wide on wasm32
+            ty if ty.starts_with('*') => "i32",
+            ty => panic!("Unexpected type `{}` in the ABI", ty),
         };
 
-        let mut dependencies: Vec<Depend> = Vec::new();
-        dependencies.push(Depend {
-            task_name: "task2".to_string(),
-            cur_field: "id".to_string(),
-            prev_field: "ids".to_string(),
-        });
-        task5.depend_on = dependencies;
-
-        let mut tasks = HashMap::new();
-        tasks.insert("task0".to_string(), task0);
-        tasks.insert("task1".to_string(), task1);
-        tasks.insert("task2".to_string(), task2);
-        tasks.insert("task3".to_string(), task3);
-        tasks.insert("task4".to_string(), task4);
-        tasks.insert("task5".to_string(), task5);
+        let params: Vec<&str> = signature
+            .inputs
+            .iter()
+            .map(|input| match input {
+                syn::FnArg::Typed(input) => wasm_type(&input.ty),
+                syn::FnArg::Receiver(_) => panic!("Unexpected receiver in the ABI"),
+            })
+            .collect();
 
-        composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+        match &signature.output {
+            syn::ReturnType::Default => format!("(param {})", params.join(" ")),
+            syn::ReturnType::Type(_, ty) => {
+                format!("(param {}) (result {})", params.join(" "), wasm_type(ty))
+            }
+        }
+    }
+
+    let has_attribute = |attrs: &[syn::Attribute], tokens: &str| {
+        attrs
+            .iter()
+            .any(|attr| quote!(#attr).to_string().contains(tokens))
+    };
+
+    let mut abi = std::collections::BTreeMap::new();
+
+    for source in [boilerplate::COMMON, boilerplate::LIB, &MockKind.library()] {
+        for item in syn::parse_file(source).unwrap().items {
+            match item {
+                syn::Item::Fn(function)
+                    if function.sig.abi.is_some()
+                        && has_attribute(&function.attrs, "no_mangle") =>
+                {
+                    abi.insert(function.sig.ident.to_string(), signature(&function.sig));
+                }
+                syn::Item::ForeignMod(foreign)
+                    if has_attribute(&foreign.attrs, "wasm_import_module = \"host\"") =>
+                {
+                    for item in foreign.items {
+                        if let syn::ForeignItem::Fn(function) = item {
+                            abi.insert(
+                                format!("host.{}", function.sig.ident),
+                                signature(&function.sig),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    abi
+}
+
+/// Module with the imports and exports of the generated workflows, their signatures
+/// taken from `workflow_abi` so that the runtime links it the way it links a workflow.
+/// It has a bump allocator and runs the given body as `_start`
+#[cfg(feature = "runtime")]
+fn workflow_module(start: &str) -> String {
+    let abi = workflow_abi();
+
+    format!(
+        r#"
+(module
+  (import "host" "set_output" (func $set_output {}))
+  (import "host" "mock_output" (func $mock_output {}))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (data (i32.const 16) "employee_ids")
+  (data (i32.const 32) "{{\"role\":\"developer\",\"limit\":2}}")
+
+  (func $memory_alloc (export "memory_alloc") {}
+    (local $ptr i32)
+    (local $pages i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (local.get $ptr) (local.get 0)))
+    (local.set $pages
+      (i32.sub
+        (i32.div_u (i32.add (global.get $heap) (i32.const 65535)) (i32.const 65536))
+        (memory.size)))
+    (if (i32.gt_s (local.get $pages) (i32.const 0))
+      (then (drop (memory.grow (local.get $pages)))))
+    (local.get $ptr))
+
+  (func (export "free_memory") {})
+
+  (func (export "_start") {}
+{}))
+"#,
+        abi["host.set_output"],
+        abi["host.mock_output"],
+        abi["memory_alloc"],
+        abi["free_memory"],
+        abi["_start"],
+        start
+    )
+}
+
+/// Body of a `_start` echoing the input of the workflow through `set_output`
+#[cfg(feature = "runtime")]
+const ECHO_START: &str = r#"    (local $output i32)
+    (local.set $output (call $memory_alloc (local.get 1) (i32.const 1)))
+    (memory.copy (local.get $output) (local.get 0) (local.get 1))
+    (call $set_output (local.get $output) (local.get 1))"#;
+
+#[cfg(feature = "runtime")]
+#[test]
+fn workflow_abi_test() {
+    let expected = [
+        ("_start", "(param i32 i32)"),
+        ("free_memory", "(param i32 i32 i32)"),
+        (
+            "host.mock_output",
+            "(param i32 i32 i32 i32 i32) (result i32)",
+        ),
+        ("host.set_output", "(param i32 i32)"),
+        ("memory_alloc", "(param i32 i32) (result i32)"),
+    ];
+
+    assert_eq!(
+        workflow_abi(),
+        expected
+            .iter()
+            .map(|(name, signature)| (name.to_string(), signature.to_string()))
+            .collect()
+    );
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn runtime_round_trip_test() {
+    let runtime = WorkflowRuntime::new(workflow_module(ECHO_START)).unwrap();
+
+    let input = serde_json::json!({"role": "developer"});
+    assert_eq!(runtime.run(&input).unwrap(), input);
+
+    let items: Vec<String> = (0..200_000).map(|i| format!("employee-{}", i)).collect();
+    let input = serde_json::json!({ "ids": items });
+    assert!(serde_json::to_vec(&input).unwrap().len() > 3 * 1024 * 1024);
+    assert_eq!(runtime.run(&input).unwrap(), input);
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn runtime_missing_output_test() {
+    let module = workflow_module(
+        &ECHO_START.replace("(call $set_output (local.get $output) (local.get 1))", ""),
+    );
+    let runtime = WorkflowRuntime::new(module).unwrap();
+
+    assert_eq!(
+        runtime.call(b"{}").err().unwrap().to_string(),
+        "The workflow did not set its output"
+    );
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn runtime_output_set_twice_test() {
+    let module = workflow_module(&format!(
+        "{}\n    (call $set_output (local.get $output) (local.get 1))",
+        ECHO_START
+    ));
+    let runtime = WorkflowRuntime::new(module).unwrap();
+
+    assert!(format!("{:?}", runtime.call(b"{}").err().unwrap())
+        .contains("The workflow set its output more than once"));
+}
+
+#[test]
+fn mock_kind_test() {
+    let config = CUSTOM_KIND_CONFIG.replace(
+        r#"    kind = "custom_runtime",
+    action_name = "greet",
+    input_arguments = [argument(name = "name", input_type = String)],
+    attributes = {"endpoint": "http://127.0.0.1:8080"},"#,
+        r#"    kind = "mock",
+    action_name = "greet",
+    input_arguments = [argument(name = "name", input_type = String)],"#,
+    );
+
+    let composer = compile_config(&config).unwrap();
+    let workflow = &composer.workflows.borrow()[0];
+    let task_kinds = composer.task_kinds.borrow();
+
+    let types_rs =
+        generate_types_rs_file_code(workflow, &composer.custom_types.borrow(), &task_kinds)
             .unwrap();
 
-        let flow = composer.workflows.borrow()[0].get_flow();
+    assert!(contains_tokens(
+        &types_rs,
+        quote!([Debug, Clone, Default, Serialize, Deserialize],)
+    ));
+    assert!(contains_tokens(
+        &types_rs,
+        quote!(
+            impl Greet {
+                pub fn run(&mut self) -> Result<(), String> {
+                    let input = to_value(&self.input).map_err(|err| err.to_string())?;
+                    self.output = mock_task_output(&self.action_name, input)?;
+                    Ok(())
+                }
+            }
+        )
+    ));
+    assert!(contains_tokens(
+        &get_kind_library_code(workflow, &task_kinds),
+        quote!(pub fn mock_task_output(action_name: &str, input: Value))
+    ));
+    assert!(contains_tokens(
+        &get_kind_library_code(workflow, &task_kinds),
+        quote!(fn mock_input_matches)
+    ));
+    assert_eq!(generate_cargo_toml_dependencies(workflow, &task_kinds), "");
+
+    let workflows = std::slice::from_ref(workflow);
+    assert!(BuildOptions::default()
+        .validate(workflows, &task_kinds)
+        .is_ok());
+    assert_eq!(
+            BuildOptions {
+                target: BuildTarget::Wasip2,
+                ..Default::default()
+            }
+            .validate(workflows, &task_kinds)
+            .unwrap_err()
+            .to_string(),
+            "Task 'greet' of workflow 'custom' has the kind mock, which cannot be built into a component"
+        );
+}
+
+#[test]
+fn mock_responses_test() {
+    let mocks: MockResponses = serde_json::from_str(
+        r#"{
+                "getsalaries": [
+                    { "input": { "id": 1 }, "output": { "salary": 1000 } },
+                    { "output": { "salary": 0 } }
+                ]
+            }"#,
+    )
+    .unwrap();
+
+    let input = serde_json::json!({ "id": 1, "name": "employee" });
+    assert_eq!(
+        mocks.response("getsalaries", &input).unwrap(),
+        &serde_json::json!({ "salary": 1000 })
+    );
+
+    let input = serde_json::json!({ "id": 2 });
+    assert_eq!(
+        mocks.response("getsalaries", &input).unwrap(),
+        &serde_json::json!({ "salary": 0 })
+    );
+
+    assert_eq!(
+        mocks.response("getaddress", &input).err().unwrap(),
+        r#"No mock response for the action 'getaddress' with the input {"id":2}"#
+    );
+}
+
+/// Body of a `_start` passing the output mocked for the `employee_ids` action as the
+/// output of the workflow
+#[cfg(feature = "runtime")]
+const MOCK_START: &str = r#"    (drop
+      (call $mock_output (i32.const 16) (i32.const 12) (i32.const 32) (i32.const 30) (i32.const 0)))
+    (call $set_output (i32.load (i32.const 0)) (i32.load (i32.const 4)))"#;
 
-        assert!(flow[0] == "task0" || flow[0] == "task4");
+#[cfg(feature = "runtime")]
+#[test]
+fn runtime_mock_output_test() {
+    let mocks: MockResponses = serde_json::from_str(
+            r#"{ "employee_ids": [{ "input": { "role": "developer" }, "output": { "ids": [1, 2] } }] }"#,
+        )
+        .unwrap();
 
-        assert!(flow[1] == "task0" || flow[1] == "task4" || flow[1] == "task2");
+    let runtime = WorkflowRuntime::new(workflow_module(MOCK_START))
+        .unwrap()
+        .with_mocks(mocks);
+    assert_eq!(
+        runtime.run(&serde_json::json!({})).unwrap(),
+        serde_json::json!({ "ids": [1, 2] })
+    );
 
-        assert!(
-            flow[2] == "task1" || flow[2] == "task2" || flow[2] == "task4" || flow[2] == "task5"
+    let runtime = WorkflowRuntime::new(workflow_module(MOCK_START)).unwrap();
+    assert_eq!(
+        String::from_utf8(runtime.call(b"{}").unwrap()).unwrap(),
+        r#"No mock response for the action 'employee_ids' with the input {"limit":2,"role":"developer"}"#
+    );
+}
+
+#[test]
+fn json_diff_test() {
+    let expected = serde_json::json!({ "ids": [1, 2], "name": "employee", "role": "developer" });
+    let actual = serde_json::json!({ "ids": [1, 3], "name": "employee", "salary": 1000 });
+
+    assert_eq!(
+        json_diff("$", &expected, &actual, false),
+        vec![
+            "$.ids[1]: expected 2, found 3",
+            "$.role: expected \"developer\", found nothing",
+            "$.salary: expected nothing, found 1000",
+        ]
+    );
+    assert_eq!(
+        json_diff("$", &expected, &actual, true),
+        vec![
+            "$.ids[1]: expected 2, found 3",
+            "$.role: expected \"developer\", found nothing",
+        ]
+    );
+}
+
+#[test]
+fn test_case_check_test() {
+    let package = tempfile::tempdir().unwrap();
+    fs::write(package.path().join("main.echo"), CUSTOM_KIND_CONFIG).unwrap();
+    fs::create_dir(package.path().join("tests")).unwrap();
+    fs::write(
+        package.path().join("tests/salary.json"),
+        r#"{
+                "input": { "role": "developer" },
+                "mocks": { "employee_ids": [{ "output": { "ids": [1, 2] } }] },
+                "output": { "1": 1000 }
+            }"#,
+    )
+    .unwrap();
+    fs::write(
+        package.path().join("tests/missing_role.json"),
+        r#"{ "error": { "kind": "input" } }"#,
+    )
+    .unwrap();
+
+    let files = SourceFiles::new(Some(package.path().to_path_buf())).unwrap();
+    assert_eq!(
+        files.tests(),
+        [
+            package.path().join("tests/missing_role.json"),
+            package.path().join("tests/salary.json")
+        ]
+    );
+
+    let missing_role = TestCase::from_file(&files.tests()[0]).unwrap();
+    let salary = TestCase::from_file(&files.tests()[1]).unwrap();
+    assert_eq!(salary.name, "salary");
+    assert_eq!(missing_role.input, serde_json::json!({}));
+
+    let success = serde_json::json!({
+        "version": 1,
+        "status": "success",
+        "result": { "1": 1000 },
+        "error": null,
+        "timings": []
+    });
+    let failure = serde_json::json!({
+        "version": 1,
+        "status": "failure",
+        "result": null,
+        "error": { "task": null, "kind": "input", "message": "missing field `role`" },
+        "timings": []
+    });
+
+    assert!(salary.check(&success).is_empty());
+    assert_eq!(
+            salary.check(&failure),
+            vec![
+                "$.status: expected \"success\", found \"failure\", with the error {\"kind\":\"input\",\"message\":\"missing field `role`\",\"task\":null}"
+            ]
         );
+    assert!(missing_role.check(&failure).is_empty());
+    assert_eq!(
+        missing_role.check(&success),
+        vec!["$.status: expected \"failure\", found \"success\""]
+    );
+}
+
+#[test]
+fn build_target_test() {
+    let target_dir = std::path::Path::new("/tmp/target");
 
-        assert!(
-            flow[3] == "task1" || flow[3] == "task2" || flow[3] == "task4" || flow[3] == "task5"
+    assert_eq!(
+        "wasm32-wasip1".parse::<BuildTarget>(),
+        Ok(BuildTarget::Wasip1)
+    );
+    assert_eq!(
+        "wasm32-wasi".parse::<BuildTarget>(),
+        Ok(BuildTarget::Wasip1)
+    );
+    assert_eq!(
+        "wasm32-wasip2".parse::<BuildTarget>(),
+        Ok(BuildTarget::Wasip2)
+    );
+    assert_eq!("native".parse::<BuildTarget>(), Ok(BuildTarget::Native));
+    assert_eq!(
+            "wasm64".parse::<BuildTarget>(),
+            Err("Unknown target 'wasm64', expected one of: wasm32-wasip1, wasm32-wasip2, wasm32-unknown-unknown, native".to_string())
         );
 
-        assert!(flow[4] == "task3" || flow[4] == "task5" || flow[4] == "task1");
+    assert_eq!(
+        BuildTarget::WasmUnknown.cargo_args(),
+        vec!["build", "--release", "--target", "wasm32-unknown-unknown"]
+    );
+    assert_eq!(
+        BuildTarget::Wasip2.artifact(target_dir, "workflow_salary_0_0_1"),
+        target_dir.join("wasm32-wasip2/release/workflow_salary_0_0_1.wasm")
+    );
+    assert_eq!(
+        BuildTarget::Native.artifact(target_dir, "workflow_salary_0_0_1"),
+        target_dir.join(format!(
+            "debug/workflow_salary_0_0_1{}",
+            std::env::consts::EXE_SUFFIX
+        ))
+    );
+    assert_eq!(
+        BuildTarget::Wasip1.output_file("salary_0.0.1"),
+        "salary_0.0.1.wasm"
+    );
+    assert_eq!(BuildTarget::Native.cargo_args(), vec!["build"]);
+    assert_eq!(BuildTarget::Native.triple(), None);
+    assert_eq!(
+        BuildTarget::Native.output_file("salary_0.0.1"),
+        format!("salary_0.0.1{}", std::env::consts::EXE_SUFFIX)
+    );
 
-        assert!(flow[5] == "task3" || flow[5] == "task5");
+    let component = BuildOptions {
+        target: BuildTarget::Wasip1,
+        component: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        component
+            .validate(&[], &TaskKinds::default())
+            .unwrap_err()
+            .to_string(),
+        "Components are built for the wasm32-wasip2 target, not for wasm32-wasip1"
+    );
+    assert!(BuildOptions {
+        target: BuildTarget::Wasip2,
+        ..Default::default()
     }
+    .is_component());
 
-    #[test]
-    fn get_attributes_test() {
-        let composer = Composer::default();
+    assert_eq!("rust".parse::<Emit>(), Ok(Emit::Rust));
+    assert_eq!(Emit::default().to_string(), "artifact");
+    assert!("llvm-ir".parse::<Emit>().is_err());
+}
 
-        let mut attributes: HashMap<String, String> = HashMap::new();
-        attributes.insert("namespace".to_string(), "value1".to_string());
-        attributes.insert("auth_key".to_string(), "value2".to_string());
+#[test]
+fn wit_file_test() {
+    let composer = compile_config(
+        r#"
+employee_ids = task(
+    kind = "hello_world",
+    action_name = "employee_ids",
+    input_arguments = [
+        argument(name = "role", input_type = String),
+        argument(name = "limit", input_type = Int, default_value = 10),
+    ],
+    output = {"ids": List(Int)},
+)
 
-        let mut tasks = HashMap::new();
-        tasks.insert(
-            "test-task".to_string(),
-            Task {
-                attributes,
+salaries = task(
+    kind = "hello_world",
+    action_name = "salaries",
+    input_arguments = [argument(name = "ids", input_type = List(Int))],
+    depend_on = [depend(task_name = "employee_ids", cur_field = "ids", prev_field = "ids")],
+    output = {"total": Int, "salaries": HashMap(Int, Int)},
+)
+
+workflows(
+    name = "employee_salary",
+    version = "0.0.1",
+    tasks = [employee_ids, salaries],
+)
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        generate_wit_file_code(&composer.workflows.borrow()[0]).unwrap(),
+        "\
+package composer:employee-salary@0.0.1;
+
+/// Workflow `employee_salary`, exchanging JSON documents
+world employee-salary {
+    /// Step of the workflow that failed
+    enum error-kind {
+        input,
+        dependency,
+        execution,
+        workflow,
+    }
+
+    /// Failure of the workflow, naming the task that failed if any
+    record workflow-error {
+        task: option<string>,
+        kind: error-kind,
+        message: string,
+    }
+
+    /// Runs the workflow on the JSON object holding its arguments, returning its result
+    ///
+    /// Arguments:
+    ///
+    /// * `role`: `String`
+    /// * `limit`: `i32`, optional
+    ///
+    /// Result:
+    ///
+    /// * `total`: `i32`
+    /// * `salaries`: `HashMap<i32,i32>`
+    export run: func(input: string) -> result<string, workflow-error>;
+}
+"
+    );
+}
+
+#[test]
+fn vendor_config_missing_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let build = tempfile::tempdir().unwrap();
+    let vendor = build.path().join("vendor");
+
+    let error = composer
+        .build_directory(
+            build.path(),
+            build.path(),
+            false,
+            &BuildOptions {
+                vendor: Some(vendor.clone()),
                 ..Default::default()
             },
-        );
+        )
+        .unwrap_err();
 
-        composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+    assert!(error.to_string().contains(&format!(
+        "Failed to read the sources of the vendored dependencies in {}",
+        vendor.display()
+    )));
+    assert!(error.to_string().contains("run `composer vendor` first"));
+}
+
+#[test]
+fn vendor_without_workflow_test() {
+    let build = tempfile::tempdir().unwrap();
+
+    let error = Composer::default()
+        .vendor_directory(build.path(), &build.path().join("vendor"), false)
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "The package does not declare any workflow to vendor"
+    );
+}
+
+#[test]
+fn vendor_failure_removes_crates_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let build = tempfile::tempdir().unwrap();
+    let vendor = build.path().join("vendor");
+    fs::write(&vendor, "").unwrap();
+
+    assert!(composer
+        .vendor_directory(build.path(), &vendor, false)
+        .is_err());
+    assert_eq!(
+        fs::read_dir(build.path()).unwrap().count(),
+        1,
+        "only the vendor file is left in the build directory"
+    );
+}
+
+#[test]
+fn get_crate_name_test() {
+    let workflow = Workflow {
+        name: "Employee-Salary".to_string(),
+        version: "0.0.1".to_string(),
+        tasks: IndexMap::new(),
+    };
+
+    assert_eq!(get_crate_name(&workflow), "workflow_employee_salary_0_0_1");
+}
+
+#[test]
+fn workflow_hash_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let custom_types = composer.custom_types.borrow();
+    let task_kinds = composer.task_kinds.borrow();
+    let workflow = composer.workflows.borrow()[0].clone();
+    let options = BuildOptions::default();
+
+    let hash = workflow_hash(&workflow, &custom_types, &task_kinds, &options);
+    assert_eq!(hash.len(), 64);
+
+    let mut moved = workflow.clone();
+    moved.tasks["salary"].location = None;
+    assert_eq!(
+        workflow_hash(&moved, &custom_types, &task_kinds, &options),
+        hash
+    );
+
+    let mut changed = workflow.clone();
+    changed.tasks["salary"].action_name = "salaries".to_string();
+    assert_ne!(
+        workflow_hash(&changed, &custom_types, &task_kinds, &options),
+        hash
+    );
+
+    let native = BuildOptions {
+        target: BuildTarget::Native,
+        ..Default::default()
+    };
+    assert_ne!(
+        workflow_hash(&workflow, &custom_types, &task_kinds, &native),
+        hash
+    );
+}
+
+#[test]
+fn build_manifest_skips_fresh_workflows_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let out = tempfile::tempdir().unwrap();
+    let output_dir = out.path().join("output");
+    let options = BuildOptions::default();
+
+    let hash = workflow_hash(
+        &composer.workflows.borrow()[0],
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+        &options,
+    );
+
+    let mut manifest = BuildManifest::default();
+    manifest
+        .artifacts
+        .insert("typed_0.0.1.wasm".to_string(), hash.clone());
+    manifest.save(&output_dir).unwrap();
+
+    assert_eq!(BuildManifest::load(&output_dir), manifest);
+    assert!(!manifest.is_fresh(&output_dir, "typed_0.0.1.wasm", &hash));
+
+    fs::write(output_dir.join("typed_0.0.1.wasm"), "built").unwrap();
+    assert!(manifest.is_fresh(&output_dir, "typed_0.0.1.wasm", &hash));
+    assert!(!manifest.is_fresh(&output_dir, "typed_0.0.1.wasm", "stale"));
+
+    // The build would fail on the missing vendor directory if it was not skipped
+    composer
+        .build_directory(
+            out.path(),
+            out.path(),
+            false,
+            &BuildOptions {
+                vendor: Some(out.path().join("vendor")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+}
+
+#[test]
+fn parse_compiler_messages_test() {
+    let output = [
+            r#"{"reason":"compiler-artifact","target":{"name":"serde"}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"unused variable: `x`","level":"warning","rendered":"warning","spans":[{"file_name":"src/types.rs","line_start":3,"is_primary":true}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"no field `name` on type `GreetInput`","level":"error","rendered":"error[E0609]","spans":[{"file_name":"src/macros.rs","line_start":32,"is_primary":true,"expansion":{"span":{"file_name":"src/macros.rs","line_start":40,"is_primary":false,"expansion":{"span":{"file_name":"src/types.rs","line_start":11,"is_primary":false}}}}}]}}"#,
+            r#"{"reason":"compiler-message","message":{"message":"aborting due to 1 previous error","level":"error","spans":[]}}"#,
+            "Hello from a build script",
+            r#"{"reason":"build-finished","success":false}"#,
+        ]
+        .join("\n");
+
+    assert_eq!(
+        parse_compiler_messages(&output),
+        vec![CompilerMessage {
+            message: "no field `name` on type `GreetInput`".to_string(),
+            rendered: Some("error[E0609]".to_string()),
+            line: Some(11),
+        }]
+    );
+}
+
+#[test]
+fn compilation_diagnostics_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let workflow = composer.workflows.borrow()[0].clone();
+    let types_rs = generate_types_rs_file_code(
+        &workflow,
+        &composer.custom_types.borrow(),
+        &composer.task_kinds.borrow(),
+    )
+    .unwrap();
+
+    let line_of = |pattern: &str, after: &str| {
+        let start = types_rs
+            .lines()
+            .position(|line| line.starts_with(after))
             .unwrap();
 
-        let composer_task = &composer.workflows.borrow()[0].tasks;
+        types_rs
+            .lines()
+            .enumerate()
+            .skip(start)
+            .find(|(_, line)| line.contains(pattern))
+            .map(|(index, _)| index + 1)
+            .unwrap()
+    };
 
-        let attributes =
-            parse_module::get_attributes(&composer_task.get("test-task").unwrap().attributes);
+    // The derive of the kind expanded by `make_main_struct!`
+    let derive = line_of("HelloWorldDerive", "    Salary, SalaryInput,");
+    assert_eq!(
+        locate_generated_line(
+            &types_rs,
+            derive,
+            "no field `details` on type `SalaryInput`",
+            &workflow
+        ),
+        Some(("salary".to_string(), Some("details".to_string())))
+    );
 
-        println!("{:#?}", attributes);
+    // The construction of the task in the main function
+    let constructor = line_of("let getaddress", "#[allow(dead_code, unused)]");
+    assert_eq!(
+        locate_generated_line(&types_rs, constructor, "", &workflow),
+        Some(("getaddress".to_string(), None))
+    );
 
-        assert!(
-            attributes == "[Namespace:\"value1\",AuthKey:\"value2\"]"
-                || attributes == "[AuthKey:\"value2\",Namespace:\"value1\"]"
-        );
-    }
+    // The input of the workflow holding the arguments of the tasks
+    let input = line_of("role : String", "    Input,");
+    assert_eq!(
+        locate_generated_line(&types_rs, input, "", &workflow),
+        Some(("employee_ids".to_string(), Some("role".to_string())))
+    );
+
+    assert_eq!(locate_generated_line(&types_rs, 0, "", &workflow), None);
+
+    let failure = BuildFailure {
+        status: "exit status: 101".to_string(),
+        messages: vec![
+            CompilerMessage {
+                message: "no field `details` on type `SalaryInput`".to_string(),
+                rendered: None,
+                line: Some(derive),
+            },
+            CompilerMessage {
+                message: "linking with `cc` failed".to_string(),
+                rendered: None,
+                line: None,
+            },
+        ],
+    };
+
+    let diagnostics = compilation_diagnostics(&failure, &types_rs, &workflow);
+    assert_eq!(diagnostics.len(), 2);
+
+    let diagnostic = &diagnostics.0[0];
+    assert_eq!(diagnostic.code, DiagnosticCode::Compilation);
+    assert_eq!(diagnostic.task.as_deref(), Some("salary"));
+    assert_eq!(diagnostic.workflow.as_deref(), Some("typed_0.0.1"));
+    assert_eq!(diagnostic.location, workflow.tasks["salary"].location);
+    assert_eq!(
+        diagnostic.hint,
+        Some(format!(
+            "check the argument 'details' of the task, used at src/types.rs:{}",
+            derive
+        ))
+    );
 
-    #[test]
-    fn get_task_kind_test_pass() {
-        let kind_name = get_task_kind("polkadot").unwrap();
-        assert_eq!(&kind_name, "Polkadot");
+    assert_eq!(diagnostics.0[1].task, None);
+    assert_eq!(diagnostics.0[1].hint, None);
 
-        let kind_name = get_task_kind("openwhisk").unwrap();
-        assert_eq!(&kind_name, "OpenWhisk");
+    let errors = BuildErrors {
+        errors: vec![Error::msg("other: Failed to build: linker not found")],
+        diagnostics: diagnostics.clone(),
+    };
+    assert!(errors
+        .to_string()
+        .ends_with(&format!("\n\n{}", diagnostics)));
+
+    let failure = BuildFailure {
+        messages: Vec::new(),
+        ..failure
+    };
+    assert_eq!(
+        compilation_diagnostics(&failure, &types_rs, &workflow).0[0].message,
+        "the generated crate fails to build: cargo build failed with exit status: 101"
+    );
+}
+
+#[test]
+fn emit_rust_generates_crates_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let build = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    composer
+        .build_directory(
+            build.path(),
+            out.path(),
+            false,
+            &BuildOptions {
+                emit: Emit::Rust,
+                target: BuildTarget::Native,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let crate_dir = build.path().join("typed_0.0.1/boilerplate");
+
+    for file in ["Cargo.toml", "src/lib.rs", "src/main.rs", "src/types.rs"] {
+        assert!(crate_dir.join(file).is_file(), "missing {}", file);
     }
 
-    #[test]
-    #[should_panic]
-    fn get_task_kind_test_fail() {
-        let kind_name = get_task_kind("polkadot").unwrap();
-        assert_eq!(&kind_name, "polkadot");
+    assert!(fs::read_to_string(crate_dir.join("Cargo.toml"))
+        .unwrap()
+        .contains("name = \"workflow_typed_0_0_1\""));
+    assert!(!out.path().join("output").join(BUILD_MANIFEST).exists());
+}
+
+#[test]
+fn build_selected_workflows_test() {
+    let config = r#"
+greet = task(
+    kind = "hello_world",
+    action_name = "greet",
+    input_arguments = [argument(name = "name", input_type = String)],
+)
+
+mocked = task(
+    kind = "mock",
+    action_name = "mocked",
+    input_arguments = [argument(name = "name", input_type = String)],
+)
+
+workflows(name = "first", version = "0.0.1", tasks = [mocked])
+workflows(name = "second", version = "0.0.1", tasks = [greet])
+"#;
+    let composer = compile_config(config).unwrap();
+    let build = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    // The mock kind of the first workflow cannot be built into a component
+    composer
+        .build_directory(
+            build.path(),
+            out.path(),
+            false,
+            &BuildOptions {
+                emit: Emit::Rust,
+                target: BuildTarget::Wasip2,
+                workflows: vec!["second_0.0.1".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let generated: Vec<_> = fs::read_dir(build.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(generated, vec!["second_0.0.1"]);
+}
+
+#[test]
+fn unknown_target_clock_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let build = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    composer
+        .build_directory(
+            build.path(),
+            out.path(),
+            false,
+            &BuildOptions {
+                emit: Emit::Rust,
+                target: BuildTarget::WasmUnknown,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let crate_dir = build.path().join("typed_0.0.1/boilerplate/src");
+    let clock = quote!(Instant::now).to_string();
+    let without_clock = quote!(not(all(target_arch = "wasm32", target_os = "unknown"))).to_string();
+
+    // `Instant::now` panics on wasm32-unknown-unknown, so it is only read by the items
+    // compiled out of this target
+    for file in fs::read_dir(crate_dir).unwrap() {
+        let file = file.unwrap().path();
+        let code = fs::read_to_string(&file).unwrap();
+
+        for item in syn::parse_file(&code).unwrap().items {
+            let tokens = quote::ToTokens::to_token_stream(&item).to_string();
+
+            if !tokens.contains(&clock) {
+                continue;
+            }
+
+            let is_excluded = match &item {
+                syn::Item::Fn(function) => function.attrs.iter().any(|attr| {
+                    attr.path().is_ident("cfg")
+                        && attr
+                            .parse_args::<proc_macro2::TokenStream>()
+                            .unwrap()
+                            .to_string()
+                            == without_clock
+                }),
+                _ => false,
+            };
+
+            assert!(
+                is_excluded,
+                "{} reads the clock on wasm32-unknown-unknown",
+                file.display()
+            );
+        }
     }
 }
+
+#[test]
+fn expand_workflow_test() {
+    let composer = compile_config(TYPED_OUTPUT_CONFIG).unwrap();
+    let build = tempfile::tempdir().unwrap();
+
+    let expanded = composer
+        .expand_workflow(
+            build.path(),
+            "typed_0.0.1",
+            false,
+            false,
+            &BuildOptions::default(),
+        )
+        .unwrap();
+
+    assert!(expanded.starts_with("// Cargo.toml [dependencies]\nhello_world_macro"));
+    assert!(expanded.contains("// src/types.rs\nuse super::*;"));
+    assert!(expanded.contains("impl_execute_trait!("));
+
+    assert_eq!(
+        composer
+            .expand_workflow(
+                build.path(),
+                "typed_0.0.2",
+                false,
+                false,
+                &BuildOptions::default()
+            )
+            .unwrap_err()
+            .to_string(),
+        "Workflow 'typed_0.0.2' not found"
+    );
+}
+
+#[test]
+fn expanded_module_test() {
+    let expanded = r#"
+            mod common { pub fn helper() {} }
+            mod types {
+                use super::*;
+                pub struct SalaryInput { id: i32 }
+                #[automatically_derived]
+                impl ::core::clone::Clone for SalaryInput {
+                    fn clone(&self) -> SalaryInput { SalaryInput { id: self.id } }
+                }
+                const _: () = {
+                    extern crate serde as _serde;
+                };
+                impl Salary {
+                    pub fn output(&self) -> Value { self.output.clone() }
+                }
+            }
+        "#;
+
+    let module = expanded_module(expanded, "types").unwrap();
+
+    assert!(module.contains("pub struct SalaryInput"));
+    assert!(module.contains("impl Salary {"));
+    assert!(!module.contains("Clone"));
+    assert!(!module.contains("serde"));
+    assert!(!module.contains("helper"));
+
+    assert!(expanded_module(expanded, "macros").is_err());
+    assert!(expanded_module("mod types {", "types").is_err());
+}
@@ -0,0 +1,303 @@
+//! Runs workflows on the boilerplate of the generated crates, its sources being compiled
+//! into the tests along with tasks written the way `types.rs` declares them
+
+// The boilerplate is compiled with the allows of its `lib.rs`
+#![allow(
+    dead_code,
+    unused_imports,
+    unused_variables,
+    unused_must_use,
+    clippy::needless_update
+)]
+
+// Imported the way the `lib.rs` of the boilerplate does, its modules using `super::*`
+use core::alloc::Layout;
+use dyn_clone::{clone_trait_object, DynClone};
+use paste::paste;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, to_value, Value};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use workflow_macro::Flow;
+extern crate alloc;
+
+#[path = "../boilerplate/src/common.rs"]
+mod common;
+#[path = "../boilerplate/src/macros.rs"]
+mod macros;
+#[path = "../boilerplate/src/traits.rs"]
+mod traits;
+
+use crate::{
+    impl_execute_trait, impl_new, impl_setter, make_input_struct, make_main_struct, output_field,
+};
+use common::*;
+use traits::*;
+
+make_input_struct!(Input, [role: String], [Debug, Clone, Default, Serialize, Deserialize]);
+
+make_input_struct!(
+    EmployeesInput,
+    [role: String],
+    [Debug, Clone, Default, Serialize, Deserialize]
+);
+make_main_struct!(
+    Employees,
+    EmployeesInput,
+    [Debug, Clone, Default, Serialize, Deserialize],
+    [],
+    output
+);
+impl_new!(Employees, EmployeesInput, [role: String]);
+impl_setter!(Employees, []);
+
+impl Employees {
+    /// Fails or returns an output not matching the input of `Salaries`, depending on the role
+    fn run(&mut self) -> Result<(), String> {
+        self.output = match self.input.role.as_str() {
+            "failing" => return Err("the directory is unreachable".to_string()),
+            "missing" => json!({}),
+            "mismatch" => json!({ "ids": "1, 2" }),
+            _ => json!({ "ids": [1, 2] }),
+        };
+
+        Ok(())
+    }
+}
+
+make_input_struct!(
+    SalariesInput,
+    [ids: Vec<i32>],
+    [Debug, Clone, Default, Serialize, Deserialize]
+);
+make_main_struct!(
+    Salaries,
+    SalariesInput,
+    [Debug, Clone, Default, Serialize, Deserialize],
+    [],
+    output
+);
+impl_new!(Salaries, SalariesInput, []);
+impl_setter!(Salaries, [ids: "ids" => "employees"]);
+
+impl Salaries {
+    fn run(&mut self) -> Result<(), String> {
+        self.output = json!({ "total": self.input.ids.iter().sum::<i32>() * 100 });
+
+        Ok(())
+    }
+}
+
+impl_execute_trait!(Employees, Salaries);
+
+/// Workflow of `employees` piped into `salaries`, as generated by `get_main_method_code`
+fn main(args: Value) -> Result<Value, String> {
+    let mut workflow = WorkflowGraph::new(2);
+    let input: Input = serde_json::from_value(args)
+        .map_err(|e| record_error(None, WorkflowErrorKind::Input, e.to_string()))?;
+    let employees = Employees::new(input.role, "employees".to_string());
+    let salaries = Salaries::new("salaries".to_string());
+    let employees_index = workflow.add_node(Box::new(employees));
+    let salaries_index = workflow.add_node(Box::new(salaries));
+    workflow.add_edges(&[(employees_index, salaries_index)]);
+    let result = workflow.init()?.term(Some(salaries_index))?;
+    Ok(result)
+}
+
+/// Serializes the envelope the way `run_workflow` of the boilerplate builds it, leaving out
+/// the durations of the timings which depend on the clock
+fn run_workflow(input: &str) -> Value {
+    let result = serde_json::from_str(input)
+        .map_err(|err| {
+            record_error(
+                None,
+                WorkflowErrorKind::Input,
+                format!("Invalid input JSON: {}", err),
+            )
+        })
+        .and_then(main);
+
+    let mut output = to_value(Output::new(result)).unwrap();
+    for timing in output["timings"].as_array_mut().unwrap() {
+        assert!(timing["duration_us"].is_u64());
+        timing.as_object_mut().unwrap().remove("duration_us");
+    }
+
+    output
+}
+
+#[test]
+fn output_success_test() {
+    assert_eq!(
+        run_workflow(r#"{ "role": "engineer" }"#),
+        json!({
+            "version": OUTPUT_VERSION,
+            "status": "success",
+            "result": { "total": 300 },
+            "error": null,
+            "timings": [{ "task": "employees" }, { "task": "salaries" }],
+        })
+    );
+    assert_eq!(OUTPUT_VERSION, 1);
+}
+
+#[test]
+fn output_input_error_test() {
+    assert_eq!(
+        run_workflow(r#"{ "role": "#),
+        json!({
+            "version": 1,
+            "status": "failure",
+            "result": null,
+            "error": {
+                "task": null,
+                "kind": "input",
+                "message": "Invalid input JSON: EOF while parsing a value at line 1 column 10",
+            },
+            "timings": [],
+        })
+    );
+    assert_eq!(
+        run_workflow(r#"{ "name": "engineer" }"#),
+        json!({
+            "version": 1,
+            "status": "failure",
+            "result": null,
+            "error": {
+                "task": null,
+                "kind": "input",
+                "message": "missing field `role`",
+            },
+            "timings": [],
+        })
+    );
+}
+
+#[test]
+fn output_execution_error_test() {
+    assert_eq!(
+        run_workflow(r#"{ "role": "failing" }"#),
+        json!({
+            "version": 1,
+            "status": "failure",
+            "result": null,
+            "error": {
+                "task": "employees",
+                "kind": "execution",
+                "message": "the directory is unreachable",
+            },
+            "timings": [{ "task": "employees" }],
+        })
+    );
+}
+
+#[test]
+fn output_dependency_error_test() {
+    assert_eq!(
+        run_workflow(r#"{ "role": "missing" }"#),
+        json!({
+            "version": 1,
+            "status": "failure",
+            "result": null,
+            "error": {
+                "task": "salaries",
+                "kind": "dependency",
+                "message": "Task 'salaries' cannot set `ids`: the output of task 'employees' \
+                    has no field `ids`",
+            },
+            "timings": [{ "task": "employees" }, { "task": "salaries" }],
+        })
+    );
+}
+
+#[test]
+fn output_workflow_error_test() {
+    // A failure not recorded by a task is reported as a failure of the workflow
+    let output = to_value(Output::new(Err("the graph is empty".to_string()))).unwrap();
+
+    assert_eq!(
+        output,
+        json!({
+            "version": 1,
+            "status": "failure",
+            "result": null,
+            "error": {
+                "task": null,
+                "kind": "workflow",
+                "message": "the graph is empty",
+            },
+            "timings": [],
+        })
+    );
+}
+
+#[test]
+fn record_error_keeps_first_failure_test() {
+    let message = record_error(Some("employees"), WorkflowErrorKind::Execution, "first");
+    record_error(Some("salaries"), WorkflowErrorKind::Dependency, "second");
+
+    let report = take_report();
+    assert_eq!(message, "first");
+    assert_eq!(
+        report.error,
+        Some(WorkflowError {
+            task: Some("employees".to_string()),
+            kind: WorkflowErrorKind::Execution,
+            message: "first".to_string(),
+        })
+    );
+    assert!(take_report().error.is_none());
+}
+
+#[test]
+fn setter_type_mismatch_test() {
+    assert_eq!(
+        run_workflow(r#"{ "role": "mismatch" }"#)["error"],
+        json!({
+            "task": "salaries",
+            "kind": "dependency",
+            "message": "Task 'salaries' cannot set `ids` from the field `ids` of task 'employees': \
+                invalid type: string \"1, 2\", expected a sequence",
+        })
+    );
+}
+
+#[test]
+fn setter_error_returned_by_execute_test() {
+    // `WorkflowGraph` ignores the result of the setter, the task failing on its execution
+    let mut salaries = Salaries::new("salaries".to_string());
+    let error = salaries.set_output_to_task(json!({})).unwrap_err();
+
+    assert_eq!(
+        error,
+        "Task 'salaries' cannot set `ids`: the output of task 'employees' has no field `ids`"
+    );
+    assert_eq!(salaries.execute(), Err(error));
+    assert_eq!(salaries.output(), Value::Null);
+
+    // The error is returned once, the task being executed again on its next input
+    salaries.set_output_to_task(json!({ "ids": [3] })).unwrap();
+    salaries.execute().unwrap();
+    assert_eq!(salaries.output(), json!({ "total": 300 }));
+}
+
+#[derive(Debug, Deserialize)]
+struct EmployeesOutput {
+    ids: Vec<i32>,
+}
+
+#[test]
+fn typed_output_field_test() {
+    let value = json!({ "ids": [1, 2] });
+    let ids: Result<Vec<i32>, String> =
+        output_field!(value, "salaries", ids, "employees", ids, EmployeesOutput);
+    assert_eq!(ids, Ok(vec![1, 2]));
+
+    let value = json!({ "id": 1 });
+    let ids: Result<Vec<i32>, String> =
+        output_field!(value, "salaries", ids, "employees", ids, EmployeesOutput);
+    assert_eq!(
+        ids.unwrap_err(),
+        "Task 'salaries' cannot set `ids` from the output of task 'employees': missing field `ids`"
+    );
+}
@@ -0,0 +1,204 @@
+use super::*;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Platform the crate generated for a workflow is built for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BuildTarget {
+    /// Wasm module run by the host through the `_start` ABI, the default
+    #[default]
+    Wasip1,
+    /// WebAssembly component exporting the world generated for the workflow
+    Wasip2,
+    /// Wasm module run by the host through the `_start` ABI, without WASI
+    WasmUnknown,
+    /// Host binary running the workflow on the JSON read from stdin, built in the debug
+    /// profile so that the generated code can be stepped through with a debugger
+    Native,
+}
+
+impl BuildTarget {
+    /// Arguments passed to `cargo build` for the target
+    pub fn cargo_args(&self) -> Vec<&'static str> {
+        match self.triple() {
+            Some(triple) => vec!["build", "--release", "--target", triple],
+            None => vec!["build"],
+        }
+    }
+
+    /// Rust target triple to install with `rustup`, if the target is not the host
+    pub fn triple(&self) -> Option<&'static str> {
+        match self {
+            BuildTarget::Wasip1 => Some("wasm32-wasip1"),
+            BuildTarget::Wasip2 => Some("wasm32-wasip2"),
+            BuildTarget::WasmUnknown => Some("wasm32-unknown-unknown"),
+            BuildTarget::Native => None,
+        }
+    }
+
+    /// Path of the artifact built by cargo for the given crate in the target directory
+    pub fn artifact(&self, target_dir: &Path, crate_name: &str) -> PathBuf {
+        match self.triple() {
+            Some(triple) => target_dir
+                .join(triple)
+                .join("release")
+                .join(format!("{}.wasm", crate_name)),
+            None => target_dir.join("debug").join(format!(
+                "{}{}",
+                crate_name,
+                std::env::consts::EXE_SUFFIX
+            )),
+        }
+    }
+
+    /// Name of the file the artifact of the workflow named `<name>_<version>` is copied to
+    pub fn output_file(&self, workflow_name: &str) -> String {
+        match self {
+            BuildTarget::Native => format!("{}{}", workflow_name, std::env::consts::EXE_SUFFIX),
+            _ => format!("{}.wasm", workflow_name),
+        }
+    }
+}
+
+impl Display for BuildTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.triple().unwrap_or("native"))
+    }
+}
+
+impl FromStr for BuildTarget {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            // `wasm32-wasi` is the name of `wasm32-wasip1` before Rust 1.78
+            "wasm" | "wasm32-wasi" | "wasm32-wasip1" => Ok(BuildTarget::Wasip1),
+            "wasm32-wasip2" => Ok(BuildTarget::Wasip2),
+            "wasm32-unknown-unknown" => Ok(BuildTarget::WasmUnknown),
+            "native" => Ok(BuildTarget::Native),
+            _ => Err(format!(
+                "Unknown target '{}', expected one of: wasm32-wasip1, wasm32-wasip2, wasm32-unknown-unknown, native",
+                target
+            )),
+        }
+    }
+}
+
+/// Output of the build of the workflows of a package
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Emit {
+    /// Artifact compiled for the target, copied to the output directory, the default
+    #[default]
+    Artifact,
+    /// Rust crate generated for each workflow, left uncompiled in the build directory
+    Rust,
+}
+
+impl Display for Emit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Emit::Artifact => write!(f, "artifact"),
+            Emit::Rust => write!(f, "rust"),
+        }
+    }
+}
+
+impl FromStr for Emit {
+    type Err = String;
+
+    fn from_str(emit: &str) -> Result<Self, Self::Err> {
+        match emit {
+            "artifact" => Ok(Emit::Artifact),
+            "rust" => Ok(Emit::Rust),
+            _ => Err(format!(
+                "Unknown output '{}', expected one of: artifact, rust",
+                emit
+            )),
+        }
+    }
+}
+
+/// Options of the build of the workflows of a package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildOptions {
+    pub target: BuildTarget,
+    /// Whether to build a WebAssembly component exporting a `run` function described by
+    /// the WIT world generated for the workflow, instead of a module using the `_start` ABI
+    pub component: bool,
+    /// Whether cargo is prevented from accessing the network
+    pub offline: bool,
+    /// Directory the dependencies of the generated crates are vendored into by
+    /// `Composer::vendor_directory`, which the crates are built from
+    pub vendor: Option<PathBuf>,
+    /// Directory holding the cargo target directories shared by the generated crates with
+    /// the same dependencies, each crate using its own target directory if none
+    pub cache: Option<PathBuf>,
+    /// Whether the workflows are rebuilt even if the build manifest shows they did not
+    /// change since their last build
+    pub force: bool,
+    /// Whether the workflows are compiled, or only their crates generated
+    pub emit: Emit,
+    /// Workflows to build, named `<name>_<version>`, every workflow of the package being
+    /// built if empty
+    pub workflows: Vec<String>,
+}
+
+impl BuildOptions {
+    /// Checks that the options can be built together, and with the tasks of the workflows
+    ///
+    /// # Arguments
+    ///
+    /// * `workflows` - The workflows to build
+    /// * `task_kinds` - The registry of the task kinds known to the composer
+    ///
+    pub fn validate(&self, workflows: &[Workflow], task_kinds: &TaskKinds) -> Result<(), Error> {
+        if self.component && self.target != BuildTarget::Wasip2 {
+            return Err(Error::msg(format!(
+                "Components are built for the wasm32-wasip2 target, not for {}",
+                self.target
+            )));
+        }
+
+        if !self.is_component() {
+            return Ok(());
+        }
+
+        for workflow in workflows.iter().filter(|workflow| self.selects(workflow)) {
+            for task in workflow.tasks.values() {
+                if task_kinds
+                    .get(&task.kind)
+                    .is_some_and(|kind| !kind.supports_component())
+                {
+                    return Err(Error::msg(format!(
+                        "Task '{}' of workflow '{}' has the kind {}, which cannot be built into a component",
+                        task.action_name, workflow.name, task.kind
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Directory of the shared build cache, `COMPOSER_CACHE_DIR` if set, or `composer` in
+    /// the cache directory of the user, such as `~/.cache/composer` on Linux
+    pub fn default_cache() -> Option<PathBuf> {
+        match std::env::var_os("COMPOSER_CACHE_DIR") {
+            Some(cache) => Some(PathBuf::from(cache)),
+            None => dirs::cache_dir().map(|cache| cache.join("composer")),
+        }
+    }
+
+    /// Whether the workflow is built with these options
+    pub fn selects(&self, workflow: &Workflow) -> bool {
+        self.workflows.is_empty()
+            || self
+                .workflows
+                .contains(&format!("{}_{}", workflow.name, workflow.version))
+    }
+
+    /// Whether the build emits a component, which the `wasm32-wasip2` target always does
+    pub fn is_component(&self) -> bool {
+        self.component || self.target == BuildTarget::Wasip2
+    }
+}
@@ -0,0 +1,176 @@
+use super::*;
+
+/// Error code identifying the class of a diagnostic, rendered as `E0001`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticCode {
+    /// The config file cannot be parsed
+    Syntax = 1,
+    /// The config file fails while being evaluated
+    Evaluation = 2,
+    /// The config file or a loaded module is missing from the package
+    FileNotFound = 3,
+    /// The kind of a task is not registered to the composer
+    UnknownKind = 4,
+    /// An attribute of a task is missing, unknown or malformed
+    InvalidAttribute = 5,
+    /// A value passed to `task`, `argument`, `depend` or `EchoStruct` cannot be parsed
+    InvalidArgument = 6,
+    /// Two tasks of a workflow share the same name
+    DuplicateTask = 7,
+    /// A task depends on a task missing from its workflow
+    UnknownDependency = 8,
+    /// The dependencies of the tasks of a workflow form a cycle
+    DependencyCycle = 9,
+    /// A workflow is empty, unnamed or shares the name of another workflow
+    InvalidWorkflow = 10,
+    /// A dependency refers to a field missing from the task
+    UnknownField = 11,
+    /// A value does not match the type it is declared with
+    TypeMismatch = 12,
+    /// A custom type used by a task is not declared with `EchoStruct`
+    MissingCustomType = 13,
+    /// The dependencies of a task do not fit the shape required by its operation
+    InvalidOperation = 14,
+    /// The Rust code generated for a workflow fails to compile
+    Compilation = 15,
+    /// A name cannot be generated as a Rust identifier, or collides with another name once
+    /// generated
+    InvalidIdentifier = 16,
+}
+
+impl Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{:04}", *self as u8)
+    }
+}
+
+/// Error found in the config files of a package, pointing at the declaration causing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub message: String,
+    pub location: Option<Location>,
+    pub workflow: Option<String>,
+    pub task: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Diagnostic {
+            code,
+            message: message.into(),
+            location: None,
+            workflow: None,
+            task: None,
+            hint: None,
+        }
+    }
+
+    /// Points the diagnostic at a declaration, keeping any location already set
+    pub fn at(self, location: Option<Location>) -> Self {
+        Diagnostic {
+            location: self.location.or(location),
+            ..self
+        }
+    }
+
+    pub fn in_workflow(self, workflow: &str) -> Self {
+        Diagnostic {
+            workflow: Some(workflow.to_string()),
+            ..self
+        }
+    }
+
+    pub fn in_task(self, task: &str) -> Self {
+        Diagnostic {
+            task: Some(task.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_hint(self, hint: impl Into<String>) -> Self {
+        Diagnostic {
+            hint: Some(hint.into()),
+            ..self
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        if let Some(location) = &self.location {
+            write!(f, "\n  --> {}", location)?;
+
+            match (&self.task, &self.workflow) {
+                (Some(task), _) => write!(f, " (task '{}')", task)?,
+                (None, Some(workflow)) => write!(f, " (workflow '{}')", workflow)?,
+                (None, None) => {}
+            }
+        }
+
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  = hint: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+impl From<starlark::Error> for Diagnostic {
+    /// Converts an error raised while parsing or evaluating a config file, keeping the
+    /// diagnostic returned by the builtins and pointing it at the span of the failing call
+    fn from(error: starlark::Error) -> Self {
+        let location = error.span().cloned().map(Location::from);
+
+        let diagnostic = match error.kind() {
+            starlark::ErrorKind::Other(err) => match err.downcast_ref::<Diagnostic>() {
+                Some(diagnostic) => diagnostic.clone(),
+                None => Diagnostic::new(DiagnosticCode::Evaluation, err.to_string()),
+            },
+            starlark::ErrorKind::Lexer(err) => {
+                Diagnostic::new(DiagnosticCode::Syntax, err.to_string())
+            }
+            kind => Diagnostic::new(DiagnosticCode::Evaluation, kind.to_string()),
+        };
+
+        diagnostic.at(location)
+    }
+}
+
+/// Every diagnostic reported while compiling the config files of a package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let diagnostics: Vec<String> = self.iter().map(Diagnostic::to_string).collect();
+        write!(f, "{}", diagnostics.join("\n\n"))?;
+
+        if self.len() > 1 {
+            write!(f, "\n\n{} errors found", self.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
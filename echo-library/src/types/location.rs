@@ -0,0 +1,37 @@
+use super::*;
+use starlark::codemap::FileSpan;
+
+/// Position of a declaration inside the `.echo` config file
+#[derive(
+    Debug, Default, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize,
+)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// Byte offsets of the declaration inside the file
+    #[serde(default)]
+    pub begin: usize,
+    #[serde(default)]
+    pub end: usize,
+}
+
+impl From<FileSpan> for Location {
+    fn from(span: FileSpan) -> Self {
+        let position = span.resolve_span().begin;
+
+        Location {
+            file: span.filename().to_string(),
+            line: position.line + 1,
+            column: position.column + 1,
+            begin: span.span.begin().get() as usize,
+            end: span.span.end().get() as usize,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
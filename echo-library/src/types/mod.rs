@@ -15,12 +15,18 @@ macro_rules! impl_starlark_values {
     };
 }
 
+mod build_target;
+mod diagnostic;
 mod input;
+mod location;
 mod rust_types;
 mod task;
 mod workflow;
 
+pub use build_target::*;
+pub use diagnostic::*;
 pub use input::*;
+pub use location::*;
 pub use rust_types::*;
 pub use task::*;
 pub use workflow::*;
@@ -1,7 +1,10 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize)]
+#[derive(
+    Debug, Default, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize,
+)]
 pub enum RustType {
+    #[default]
     Null,
     Int,
     Uint,
@@ -15,9 +18,42 @@ pub enum RustType {
     Struct(String),
 }
 
-impl Default for RustType {
-    fn default() -> RustType {
-        Self::Null
+impl RustType {
+    /// Names of the custom types used by the type, including the nested ones
+    pub fn custom_types(&self) -> Vec<&str> {
+        match self {
+            RustType::List(item_type) => item_type.custom_types(),
+            RustType::Tuple(key_type, value_type) | RustType::HashMap(key_type, value_type) => {
+                let mut names = key_type.custom_types();
+                names.extend(value_type.custom_types());
+                names
+            }
+            RustType::Struct(name) => vec![name.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks whether a value of this type can be deserialized as the expected type,
+    /// `Value` being compatible with any type
+    pub fn is_compatible(&self, expected: &RustType) -> bool {
+        match (self, expected) {
+            (RustType::Value, _) | (_, RustType::Value) => true,
+            (RustType::List(item_type), RustType::List(expected_item_type)) => {
+                item_type.is_compatible(expected_item_type)
+            }
+            (
+                RustType::Tuple(key_type, value_type),
+                RustType::Tuple(expected_key_type, expected_value_type),
+            )
+            | (
+                RustType::HashMap(key_type, value_type),
+                RustType::HashMap(expected_key_type, expected_value_type),
+            ) => {
+                key_type.is_compatible(expected_key_type)
+                    && value_type.is_compatible(expected_value_type)
+            }
+            (rust_type, expected) => rust_type == expected,
+        }
     }
 }
 
@@ -1,7 +1,10 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize)]
+#[derive(
+    Debug, Default, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize,
+)]
 pub enum Operation {
+    #[default]
     Normal,
     Concat,
     Combine,
@@ -18,12 +21,6 @@ impl Operation {
     }
 }
 
-impl Default for Operation {
-    fn default() -> Operation {
-        Self::Normal
-    }
-}
-
 #[derive(
     Debug, Default, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize,
 )]
@@ -40,8 +37,25 @@ pub struct Task {
     pub kind: String,
     pub action_name: String,
     pub input_arguments: Vec<Input>,
-    pub attributes: HashMap<String, String>,
+    pub attributes: IndexMap<String, String>,
     #[serde(default)]
     pub operation: Operation,
     pub depend_on: Vec<Depend>,
+    /// Fields of the output of the task with their types, empty if not declared
+    #[serde(default)]
+    pub output: IndexMap<String, RustType>,
+    #[serde(default)]
+    pub location: Option<Location>,
+}
+
+impl Task {
+    /// Input argument a map task is run on, set to each element of the list it depends on,
+    /// or None if the task does not depend on any list
+    pub fn map_element(&self) -> Option<&Input> {
+        let dependent = self.depend_on.first()?;
+
+        self.input_arguments
+            .iter()
+            .find(|input| input.name == dependent.cur_field)
+    }
 }
@@ -4,7 +4,7 @@ use super::*;
 pub struct Workflow {
     pub name: String,
     pub version: String,
-    pub tasks: HashMap<String, Task>,
+    pub tasks: IndexMap<String, Task>,
 }
 
 impl Workflow {
@@ -19,16 +19,17 @@ impl Workflow {
     /// # Returns
     ///
     /// * `Option<Vec<String>>` - An option containing a vector of dependencies if the task is
-    ///   found, or None if the task have no dependency
+    ///   found, or None if the task is not part of the workflow
     ///
     pub fn get_dependencies(&self, task_name: &str) -> Option<Vec<String>> {
-        let mut dependencies = Vec::<String>::new();
+        let task = self.tasks.get(task_name)?;
 
-        for task in self.tasks.get(task_name).unwrap().depend_on.iter() {
-            dependencies.push(task.task_name.clone());
-        }
-
-        Some(dependencies)
+        Some(
+            task.depend_on
+                .iter()
+                .map(|depend| depend.task_name.clone())
+                .collect(),
+        )
     }
 
     /// Performs depth-first search (DFS) in the workflow subgraph.
@@ -37,51 +38,529 @@ impl Workflow {
     ///
     /// * `task_name` - A string slice that holds the name of the task where the DFS should start
     /// * `visited` - A mutable reference to a HashMap that holds the list of task (node) names
-    ///   and a boolean indicating whether it has been traversed
+    ///   and the traversal state of each task
+    /// * `path` - A mutable reference to a vector of task names that are currently being
+    ///   traversed, used to report the tasks forming a cycle
     /// * `flow` - A mutable reference to a vector of strings that stores the flow of the DFS
     ///   traversal
-    /// * `workflow_index` - An integer that holds the index of the workflow where the given
-    ///   task is located
     ///
-    fn dfs(&self, task_name: &str, visited: &mut HashMap<String, bool>, flow: &mut Vec<String>) {
-        visited.insert(task_name.to_string(), true);
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - An error if a dependency names a task that is not part of the
+    ///   workflow or if the dependencies form a cycle
+    ///
+    fn dfs(
+        &self,
+        task_name: &str,
+        visited: &mut HashMap<String, Visit>,
+        path: &mut Vec<String>,
+        flow: &mut Vec<String>,
+    ) -> Result<(), Error> {
+        visited.insert(task_name.to_string(), Visit::InProgress);
+        path.push(task_name.to_string());
+
+        let task = &self.tasks[task_name];
 
-        for depend_task in self.get_dependencies(task_name).unwrap().iter() {
-            if !visited[depend_task] {
-                self.dfs(depend_task, visited, flow);
+        for depend in task.depend_on.iter() {
+            match visited.get(&depend.task_name) {
+                None => return Err(self.unknown_dependency_error(task, depend).into()),
+                Some(Visit::InProgress) => {
+                    return Err(self.cycle_error(&depend.task_name, path));
+                }
+                Some(Visit::Done) => {}
+                Some(Visit::Pending) => self.dfs(&depend.task_name, visited, path, flow)?,
             }
         }
 
+        path.pop();
+        visited.insert(task_name.to_string(), Visit::Done);
         flow.push(task_name.to_string());
+
+        Ok(())
+    }
+
+    fn unknown_dependency_error(&self, task: &Task, depend: &Depend) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::UnknownDependency,
+            format!(
+                "Task '{}' in workflow '{}' depends on unknown task '{}'",
+                task.action_name, self.name, depend.task_name
+            ),
+        )
+        .at(task.location.clone())
+        .in_workflow(&self.name)
+        .in_task(&task.action_name)
+    }
+
+    /// Builds the error for a dependency cycle, listing the tasks in the order the data
+    /// flows between them (i.e `a -> b -> c -> a`)
+    fn cycle_error(&self, task_name: &str, path: &[String]) -> Error {
+        let start = path
+            .iter()
+            .position(|name| name == task_name)
+            .unwrap_or_default();
+
+        let mut cycle: Vec<&str> = path[start..].iter().rev().map(String::as_str).collect();
+        cycle.push(cycle[0]);
+
+        Diagnostic::new(
+            DiagnosticCode::DependencyCycle,
+            format!(
+                "Cycle detected in workflow '{}': {}",
+                self.name,
+                cycle.join(" -> ")
+            ),
+        )
+        .at(self.tasks[cycle[0]].location.clone())
+        .in_workflow(&self.name)
+        .in_task(cycle[0])
+        .into()
     }
 
     /// Performs topological sort in the workflow graph.
     /// This method is invoked by the parse_module.
     ///
-    /// # Arguments
-    ///
-    /// * `workflow_index` - An integer that holds the index of the workflow for which
-    ///   topological sort is to be performed
-    ///
     /// # Returns
     ///
-    /// * `Vec<String>` - A vector containing the list of task names in the order of the
-    ///   topological sort
+    /// * `Result<Vec<String>, Error>` - A vector containing the list of task names in the order
+    ///   of the topological sort, or an error if the dependencies of the tasks form a cycle or
+    ///   refer to a task missing from the workflow
     ///
-    pub fn get_flow(&self) -> Vec<String> {
-        let mut visited = HashMap::<String, bool>::new();
+    pub fn get_flow(&self) -> Result<Vec<String>, Error> {
+        let mut visited = HashMap::<String, Visit>::new();
         let mut flow = Vec::<String>::new();
 
         for task in self.tasks.iter() {
-            visited.insert(task.0.to_string(), false);
+            visited.insert(task.0.to_string(), Visit::Pending);
         }
 
         for task in self.tasks.iter() {
-            if !visited[task.0] {
-                self.dfs(task.0, &mut visited, &mut flow)
+            if visited[task.0] == Visit::Pending {
+                self.dfs(task.0, &mut visited, &mut Vec::new(), &mut flow)?
+            }
+        }
+
+        Ok(flow)
+    }
+
+    /// Checks the dependencies of the workflow, reporting every dependency on a task
+    /// missing from the workflow, or a cycle between the tasks if there is none
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Diagnostic>` - The problems found in the workflow, empty if the workflow is valid
+    ///
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for task in self.tasks.values() {
+            for depend in task.depend_on.iter() {
+                if !self.tasks.contains_key(&depend.task_name) {
+                    diagnostics.push(self.unknown_dependency_error(task, depend));
+                }
+            }
+        }
+
+        if diagnostics.is_empty() {
+            if let Err(err) = self.get_flow() {
+                diagnostics.push(err.downcast::<Diagnostic>().unwrap_or_else(|err| {
+                    Diagnostic::new(DiagnosticCode::InvalidWorkflow, err.to_string())
+                }));
+            }
+        }
+
+        if diagnostics.is_empty() {
+            for task in self.tasks.values() {
+                diagnostics.extend(self.check_types(task));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Checks that the names of the tasks, of their arguments and of the EchoStructs they
+    /// use can be generated together. Tasks with different names, such as `my-task` and
+    /// `my_task`, can be converted into the same Rust types, the types can shadow the
+    /// names the generated code imports, such as `Value`, and the arguments of every task
+    /// are read from the same `Input` of the workflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `custom_types` - The EchoStructs declared by the config files, by name
+    /// * `task_kinds` - The registry of the task kinds known to the composer
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Diagnostic>` - The collisions found in the workflow
+    ///
+    pub fn check_names(
+        &self,
+        custom_types: &IndexMap<String, String>,
+        task_kinds: &TaskKinds,
+    ) -> Vec<Diagnostic> {
+        // Invalid code of a kind is reported while generating the workflow
+        let scope = get_generated_scope(self, task_kinds).unwrap_or_default();
+
+        let mut diagnostics = Vec::new();
+        let mut generated_names = HashMap::<String, &str>::new();
+        let mut arguments = HashMap::<&str, (&str, &Input)>::new();
+        let mut shadowing_types = IndexSet::<&str>::new();
+
+        for task in self.tasks.values() {
+            let diagnostic = |message: String, hint: &str| {
+                Diagnostic::new(DiagnosticCode::InvalidIdentifier, message)
+                    .at(task.location.clone())
+                    .in_workflow(&self.name)
+                    .in_task(&task.action_name)
+                    .with_hint(hint)
+            };
+
+            let type_name = task.action_name.to_case(Case::Pascal);
+
+            let generated = [
+                (format!("{}Input", type_name), true),
+                (format!("{}Output", type_name), true),
+                (task.action_name.to_case(Case::Snake), false),
+                (type_name, true),
+            ];
+
+            let collision = generated.into_iter().find_map(|(generated, is_type)| {
+                if custom_types.contains_key(&generated) {
+                    return Some(diagnostic(
+                        format!(
+                            "Task '{}' is generated as `{}`, the name of an EchoStruct",
+                            task.action_name, generated
+                        ),
+                        "rename the task or the EchoStruct",
+                    ));
+                }
+
+                if is_type && scope.contains(&generated) {
+                    return Some(diagnostic(
+                        format!(
+                            "Task '{}' is generated as `{}`, which is already in scope of the generated code",
+                            task.action_name, generated
+                        ),
+                        "rename the task, whose type would shadow the item of the generated code",
+                    ));
+                }
+
+                match generated_names.insert(generated.clone(), &task.action_name) {
+                    Some(other) if other != task.action_name => Some(diagnostic(
+                        format!(
+                            "Tasks '{}' and '{}' are both generated as `{}`",
+                            other, task.action_name, generated
+                        ),
+                        "rename the task so that its name differs by more than its case and separators",
+                    )),
+                    _ => None,
+                }
+            });
+
+            diagnostics.extend(collision);
+
+            let used_types = task
+                .input_arguments
+                .iter()
+                .map(|input| &input.input_type)
+                .chain(task.output.values())
+                .flat_map(RustType::custom_types);
+
+            for name in used_types {
+                if scope.contains(name) && shadowing_types.insert(name) {
+                    diagnostics.push(diagnostic(
+                        format!(
+                            "EchoStruct `{}` is already in scope of the generated code",
+                            name
+                        ),
+                        "rename the EchoStruct, whose type would shadow the item of the generated code",
+                    ));
+                }
+            }
+
+            for input in task.input_arguments.iter().filter(|input| !input.is_depend) {
+                let (other, other_input) = match arguments.get(input.name.as_str()) {
+                    Some(argument) => *argument,
+                    None => {
+                        arguments.insert(&input.name, (&task.action_name, input));
+                        continue;
+                    }
+                };
+
+                let message = if other_input.input_type != input.input_type {
+                    format!(
+                        "Tasks '{}' and '{}' both take the argument `{}`, as `{}` and `{}`",
+                        other,
+                        task.action_name,
+                        input.name,
+                        other_input.input_type,
+                        input.input_type
+                    )
+                } else if other_input.default_value != input.default_value {
+                    format!(
+                        "Tasks '{}' and '{}' both take the argument `{}`, with different default values",
+                        other, task.action_name, input.name
+                    )
+                } else {
+                    continue;
+                };
+
+                diagnostics.push(diagnostic(
+                    message,
+                    "the arguments of the tasks are read from the same input of the workflow, so the arguments sharing a name must share their type and default value",
+                ));
             }
         }
 
-        flow
+        diagnostics
     }
+
+    /// Checks the fields of the dependencies of a task against the outputs declared by
+    /// the tasks it depends on, following the shape of the data handed over by each
+    /// operation. Fields of tasks without a declared output are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task whose dependencies are checked
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Diagnostic>` - The mismatches found in the dependencies of the task
+    ///
+    fn check_types(&self, task: &Task) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let diagnostic = |code: DiagnosticCode, message: String| {
+            Diagnostic::new(code, message)
+                .at(task.location.clone())
+                .in_workflow(&self.name)
+                .in_task(&task.action_name)
+        };
+
+        if let Operation::Map(field) = &task.operation {
+            if !task.output.is_empty() && !task.output.contains_key(field) {
+                diagnostics.push(unknown_output_field(task, field).at(task.location.clone()));
+            }
+        }
+
+        if task.operation == Operation::Concat {
+            if let Some(diagnostic) = self.check_concat(task) {
+                diagnostics.push(diagnostic);
+            }
+
+            return diagnostics;
+        }
+
+        for depend in task.depend_on.iter() {
+            let upstream = &self.tasks[&depend.task_name];
+
+            let input = match task
+                .input_arguments
+                .iter()
+                .find(|input| input.name == depend.cur_field)
+            {
+                Some(input) => input,
+                None => continue,
+            };
+
+            let produced = if upstream.operation.is_map() {
+                if !task.operation.is_combine() {
+                    continue;
+                }
+
+                match map_output_type(upstream) {
+                    Some(produced) => produced,
+                    None => continue,
+                }
+            } else {
+                match output_field_type(upstream, &depend.prev_field) {
+                    Ok(Some(produced)) => produced,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        diagnostics.push(
+                            (*err)
+                                .at(task.location.clone())
+                                .in_workflow(&self.name)
+                                .in_task(&task.action_name),
+                        );
+                        continue;
+                    }
+                }
+            };
+
+            let expected = if task.operation.is_map() {
+                RustType::List(Box::new(input.input_type.clone()))
+            } else {
+                input.input_type.clone()
+            };
+
+            if !produced.is_compatible(&expected) {
+                let mismatch = diagnostic(
+                    DiagnosticCode::TypeMismatch,
+                    format!(
+                        "Field `{}` of task '{}' is `{}`, but the input `{}` expects `{}`",
+                        depend.prev_field, depend.task_name, produced, input.name, expected
+                    ),
+                );
+
+                diagnostics.push(if task.operation.is_map() {
+                    mismatch.with_hint(
+                        "a map task depends on a list of elements of its input argument type",
+                    )
+                } else {
+                    mismatch
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Checks a concat task, which joins the maps produced by exactly two tasks on their
+    /// keys into a map of the key to the tuple of both values
+    fn check_concat(&self, task: &Task) -> Option<Diagnostic> {
+        let diagnostic = |code: DiagnosticCode, message: String| {
+            Diagnostic::new(code, message)
+                .at(task.location.clone())
+                .in_workflow(&self.name)
+                .in_task(&task.action_name)
+        };
+
+        if task.depend_on.len() != 2 {
+            return Some(
+                diagnostic(
+                    DiagnosticCode::InvalidOperation,
+                    format!(
+                        "Concat task '{}' must depend on exactly two tasks, found {}",
+                        task.action_name,
+                        task.depend_on.len()
+                    ),
+                )
+                .with_hint("a concat task joins the maps produced by two tasks"),
+            );
+        }
+
+        let mut produced = Vec::new();
+
+        for depend in task.depend_on.iter() {
+            let upstream = &self.tasks[&depend.task_name];
+
+            let rust_type = if upstream.operation.is_map() {
+                map_output_type(upstream)
+            } else {
+                match output_field_type(upstream, &depend.prev_field) {
+                    Ok(rust_type) => rust_type,
+                    Err(err) => {
+                        return Some(
+                            (*err)
+                                .at(task.location.clone())
+                                .in_workflow(&self.name)
+                                .in_task(&task.action_name),
+                        )
+                    }
+                }
+            };
+
+            match rust_type {
+                Some(RustType::HashMap(key_type, value_type)) => {
+                    produced.push((*key_type, *value_type))
+                }
+                Some(RustType::Value) | None => return None,
+                Some(rust_type) => {
+                    return Some(diagnostic(
+                        DiagnosticCode::TypeMismatch,
+                        format!(
+                            "Concat task '{}' expects a map from task '{}', found `{}`",
+                            task.action_name, depend.task_name, rust_type
+                        ),
+                    ))
+                }
+            }
+        }
+
+        let input = task
+            .input_arguments
+            .iter()
+            .find(|input| input.name == task.depend_on[0].cur_field)?;
+
+        let expected = RustType::HashMap(
+            Box::new(produced[0].0.clone()),
+            Box::new(RustType::Tuple(
+                Box::new(produced[0].1.clone()),
+                Box::new(produced[1].1.clone()),
+            )),
+        );
+
+        if !produced[0].0.is_compatible(&produced[1].0) {
+            return Some(diagnostic(
+                DiagnosticCode::TypeMismatch,
+                format!(
+                    "Concat task '{}' joins maps with different keys: `{}` and `{}`",
+                    task.action_name, produced[0].0, produced[1].0
+                ),
+            ));
+        }
+
+        if !expected.is_compatible(&input.input_type) {
+            return Some(diagnostic(
+                DiagnosticCode::TypeMismatch,
+                format!(
+                    "Concat task '{}' produces `{}`, but the input `{}` expects `{}`",
+                    task.action_name, expected, input.name, input.input_type
+                ),
+            ));
+        }
+
+        None
+    }
+}
+
+/// Type of the map produced by a map task, from each element of its input to the mapped
+/// field of its output, or None if the task does not declare its output
+fn map_output_type(task: &Task) -> Option<RustType> {
+    let field = match &task.operation {
+        Operation::Map(field) => field,
+        _ => return None,
+    };
+
+    Some(RustType::HashMap(
+        Box::new(task.map_element()?.input_type.clone()),
+        Box::new(task.output.get(field)?.clone()),
+    ))
+}
+
+/// Type of a field of the output of a task, or None if the task does not declare its output
+fn output_field_type(task: &Task, field: &str) -> Result<Option<RustType>, Box<Diagnostic>> {
+    if task.output.is_empty() {
+        return Ok(None);
+    }
+
+    match task.output.get(field) {
+        Some(rust_type) => Ok(Some(rust_type.clone())),
+        None => Err(Box::new(unknown_output_field(task, field))),
+    }
+}
+
+fn unknown_output_field(task: &Task, field: &str) -> Diagnostic {
+    let fields: Vec<&str> = task.output.keys().map(String::as_str).collect();
+
+    Diagnostic::new(
+        DiagnosticCode::UnknownField,
+        format!(
+            "Unknown field `{}` in the output of task '{}'",
+            field, task.action_name
+        ),
+    )
+    .with_hint(format!(
+        "the output of task '{}' declares: {}",
+        task.action_name,
+        fields.join(", ")
+    ))
+}
+
+/// Traversal state of a task while sorting the workflow graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    Pending,
+    InProgress,
+    Done,
 }